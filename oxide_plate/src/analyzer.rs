@@ -0,0 +1,264 @@
+//! Spectrum and decay analysis of the wet signal for the editor.
+//!
+//! The audio thread pushes samples into a [`SampleRing`] without locking or allocating,
+//! and the editor reads the latest window from it to feed an [`Analyzer`] and a [`Waterfall`].
+
+use nih_plug::prelude::{util, AtomicF32};
+use realfft::{num_complex::Complex, RealFftPlanner, RealToComplex};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// Length of an analysis frame.
+pub const FFT_SIZE: usize = 2048;
+
+/// Number of spectra kept by the waterfall.
+pub const DEPTH: usize = 48;
+
+/// Lowest level reported, in dBFS.
+pub const FLOOR_DB: f32 = -120.0;
+
+/// A single-producer ring of the most recent samples.
+///
+/// Writers never wait for readers; a reader that falls behind simply sees newer samples.
+#[derive(Debug)]
+pub struct SampleRing {
+    samples: Box<[AtomicF32]>,
+    written: AtomicUsize,
+}
+
+impl SampleRing {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: (0..capacity).map(|_| AtomicF32::new(0.0)).collect(),
+            written: AtomicUsize::new(0),
+        }
+    }
+
+    /// Appends a sample. Must only be called from one thread.
+    pub fn push(&self, x: f32) {
+        let written = self.written.load(Ordering::Relaxed);
+        self.samples[written % self.samples.len()].store(x, Ordering::Relaxed);
+        self.written
+            .store(written.wrapping_add(1), Ordering::Release);
+    }
+
+    /// Copies the most recent samples into `out`, oldest first,
+    /// padding with zeros until enough samples were pushed.
+    /// Returns the number of samples pushed so far.
+    ///
+    /// ## Panics
+    /// - If `out` is longer than the capacity.
+    pub fn read_latest(&self, out: &mut [f32]) -> usize {
+        if out.len() > self.samples.len() {
+            panic!("read is longer than the ring");
+        }
+        let written = self.written.load(Ordering::Acquire);
+        let missing = out.len().saturating_sub(written);
+        let (zeros, rest) = out.split_at_mut(missing);
+        zeros.fill(0.0);
+        let start = written - rest.len();
+        for (i, y) in rest.iter_mut().enumerate() {
+            *y = self.samples[(start + i) % self.samples.len()].load(Ordering::Relaxed);
+        }
+        written
+    }
+}
+
+/// Hann windowed magnitude spectra.
+pub struct Analyzer {
+    fft: Arc<dyn RealToComplex<f32>>,
+    window: Vec<f32>,
+    frame: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl Analyzer {
+    pub fn new(size: usize) -> Self {
+        let fft = RealFftPlanner::new().plan_fft_forward(size);
+        let window = (0..size)
+            .map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / size as f32).cos())
+            .collect();
+        Self {
+            window,
+            frame: fft.make_input_vec(),
+            spectrum: fft.make_output_vec(),
+            scratch: fft.make_scratch_vec(),
+            fft,
+        }
+    }
+
+    /// Number of bins of a spectrum.
+    pub fn bins(&self) -> usize {
+        self.spectrum.len()
+    }
+
+    /// Writes the level of each bin in dBFS into `out`,
+    /// scaled so that a full scale sine reads 0 dB.
+    ///
+    /// ## Panics
+    /// - If the lengths don't match the frame size and [`Analyzer::bins`].
+    pub fn spectrum(&mut self, samples: &[f32], out: &mut [f32]) {
+        assert_eq!(self.frame.len(), samples.len());
+        assert_eq!(self.spectrum.len(), out.len());
+        for ((x, w), y) in samples.iter().zip(&self.window).zip(&mut self.frame) {
+            *y = x * w;
+        }
+        self.fft
+            .process_with_scratch(&mut self.frame, &mut self.spectrum, &mut self.scratch)
+            .unwrap();
+        let gain = 2.0 / self.window.iter().sum::<f32>();
+        for (bin, y) in self.spectrum.iter().zip(out) {
+            *y = util::gain_to_db(bin.norm() * gain).max(FLOOR_DB);
+        }
+    }
+}
+
+/// Frequency of a bin of a spectrum of `size` samples.
+pub fn frequency(bin: usize, size: usize, sample_rate: f32) -> f32 {
+    bin as f32 * sample_rate / size as f32
+}
+
+/// Reduces a spectrum to `count` logarithmically spaced bands from 20 Hz to Nyquist,
+/// keeping the loudest bin of each as `(frequency, dB)`.
+pub fn bands(spectrum: &[f32], sample_rate: f32, count: usize) -> Vec<(f32, f32)> {
+    let size = 2 * (spectrum.len() - 1);
+    let nyquist = sample_rate / 2.0;
+    let edge = |i: usize| 20.0 * (nyquist / 20.0).powf(i as f32 / count as f32);
+    (0..count)
+        .map(|i| {
+            let (low, high) = (edge(i), edge(i + 1));
+            let bins = spectrum.iter().enumerate().filter(|(bin, _)| {
+                let f = frequency(*bin, size, sample_rate);
+                low <= f && f < high
+            });
+            let level = bins.map(|(_, db)| *db).fold(FLOOR_DB, f32::max);
+            ((low * high).sqrt(), level)
+        })
+        .collect()
+}
+
+/// The most recent spectra, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct Waterfall {
+    frames: VecDeque<Vec<f32>>,
+}
+
+impl Waterfall {
+    pub fn push(&mut self, spectrum: &[f32]) {
+        let mut frame = if self.frames.len() == DEPTH {
+            self.frames.pop_front().unwrap()
+        } else {
+            Vec::new()
+        };
+        frame.clear();
+        frame.extend_from_slice(spectrum);
+        self.frames.push_back(frame);
+    }
+
+    pub fn frames(&self) -> impl ExactSizeIterator<Item = &Vec<f32>> {
+        self.frames.iter()
+    }
+
+    /// Energy decay relief of the kept spectra: the energy remaining in each bin
+    /// from each frame on, in dB relative to the total energy of the loudest bin.
+    pub fn energy_decay_relief(&self) -> Vec<Vec<f32>> {
+        let bins = self.frames.front().map_or(0, Vec::len);
+        let mut remaining = vec![0.0f32; bins];
+        let mut relief: Vec<Vec<f32>> = self
+            .frames
+            .iter()
+            .rev()
+            .map(|frame| {
+                for (energy, db) in remaining.iter_mut().zip(frame) {
+                    *energy += util::db_to_gain(*db).powi(2);
+                }
+                remaining.clone()
+            })
+            .collect();
+        relief.reverse();
+
+        let total = remaining.iter().copied().fold(0.0, f32::max);
+        for frame in &mut relief {
+            for energy in frame.iter_mut() {
+                *energy = (10.0 * (*energy / total).log10()).max(FLOOR_DB);
+            }
+        }
+        relief
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring() {
+        let ring = SampleRing::new(8);
+        let mut out = [1.0; 4];
+        assert_eq!(0, ring.read_latest(&mut out));
+        assert_eq!([0.0; 4], out);
+
+        for x in 0..3 {
+            ring.push(x as f32);
+        }
+        assert_eq!(3, ring.read_latest(&mut out));
+        assert_eq!([0.0, 0.0, 1.0, 2.0], out);
+
+        for x in 3..10 {
+            ring.push(x as f32);
+        }
+        assert_eq!(10, ring.read_latest(&mut out));
+        assert_eq!([6.0, 7.0, 8.0, 9.0], out);
+        let mut all = [0.0; 8];
+        ring.read_latest(&mut all);
+        assert_eq!([2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0], all);
+    }
+
+    #[test]
+    fn sine() {
+        let mut analyzer = Analyzer::new(FFT_SIZE);
+        let bin = 100;
+        let samples: Vec<f32> = (0..FFT_SIZE)
+            .map(|t| 0.5 * (std::f32::consts::TAU * (bin * t) as f32 / FFT_SIZE as f32).sin())
+            .collect();
+        let mut spectrum = vec![0.0; analyzer.bins()];
+        analyzer.spectrum(&samples, &mut spectrum);
+
+        assert!((spectrum[bin] + 6.02).abs() < 0.05);
+        assert!(spectrum[bin + 10] < -80.0);
+        assert_eq!(FFT_SIZE / 2 + 1, spectrum.len());
+
+        let bands = bands(&spectrum, 48000.0, 32);
+        assert_eq!(32, bands.len());
+        let loudest = bands.iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+        let f = frequency(bin, FFT_SIZE, 48000.0);
+        assert!(loudest.0 / f < 1.2 && f / loudest.0 < 1.2);
+    }
+
+    #[test]
+    fn decay_relief() {
+        let mut waterfall = Waterfall::default();
+        for t in 0..DEPTH + 4 {
+            // The low bin decays by 1 dB and the high one by 6 dB per frame.
+            waterfall.push(&[-(t as f32), -6.0 * t as f32]);
+        }
+        assert_eq!(DEPTH, waterfall.frames().len());
+        assert_eq!(-6.0 * 4.0, waterfall.frames().next().unwrap()[1]);
+
+        let relief = waterfall.energy_decay_relief();
+        assert_eq!(DEPTH, relief.len());
+        assert_eq!(0.0, relief[0][0]);
+        for frames in relief.windows(2) {
+            assert!(frames[1][0] < frames[0][0]);
+            assert!(frames[1][1] <= frames[0][1]);
+        }
+        assert!((relief[1][0] + 1.0).abs() < 1e-3);
+        assert!(relief[1][1] < relief[1][0]);
+    }
+}
@@ -0,0 +1,605 @@
+use nih_plug::prelude::Enum;
+use oxide_plate_dsp::wav;
+use realfft::{num_complex::Complex, RealFftPlanner};
+use rustfft::{Fft, FftDirection, FftPlanner};
+use std::{path::Path, sync::Arc};
+
+/// Length of the smallest partition, which is also the length of the direct-form head.
+pub const PARTITION: usize = 64;
+
+/// Ratio between consecutive partition sizes of the non-uniform scheme.
+const GROWTH: usize = 8;
+
+/// Zero crossings on each side of the sinc that stretches and resamples impulse responses.
+const SINC_ZEROS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum Partitioning {
+    /// Every partition has the smallest size.
+    Uniform,
+    /// Partitions grow along the tail, which is cheaper for long responses.
+    #[name = "Non-uniform"]
+    NonUniform,
+}
+
+/// Impulse response with one buffer per channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpulseResponse {
+    pub channels: Vec<Vec<f32>>,
+    pub sample_rate: f32,
+}
+
+impl ImpulseResponse {
+    /// Reads a WAV file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, wav::Error> {
+        let audio = wav::Audio::read(path)?;
+        Ok(Self {
+            channels: audio.channels,
+            sample_rate: audio.sample_rate as f32,
+        })
+    }
+
+    /// Length in samples.
+    pub fn len(&self) -> usize {
+        self.channels.first().map_or(0, Vec::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Keeps `length` samples beginning at `start`.
+    pub fn trim(&mut self, start: usize, length: usize) {
+        for channel in self.channels.iter_mut() {
+            let start = start.min(channel.len());
+            let end = start.saturating_add(length).min(channel.len());
+            channel.truncate(end);
+            channel.drain(..start);
+        }
+    }
+
+    /// Reverses in time.
+    pub fn reverse(&mut self) {
+        for channel in self.channels.iter_mut() {
+            channel.reverse();
+        }
+    }
+
+    /// Stretches in time by `factor` with windowed sinc interpolation,
+    /// filtering out what would fold over when shortening.
+    pub fn stretch(&mut self, factor: f32) {
+        let factor = factor as f64;
+        // The cutoff relative to the source's Nyquist frequency.
+        let cutoff = factor.min(1.0);
+        let half_width = SINC_ZEROS as f64 / cutoff;
+        for channel in self.channels.iter_mut() {
+            let length = (channel.len() as f64 * factor).round() as usize;
+            *channel = (0..length)
+                .map(|n| {
+                    let position = n as f64 / factor;
+                    let first = (position - half_width).ceil().max(0.0) as usize;
+                    let end = ((position + half_width) as usize + 1).min(channel.len());
+                    let y: f64 = (first..end)
+                        .map(|k| {
+                            let t = k as f64 - position;
+                            channel[k] as f64 * cutoff * sinc(cutoff * t) * blackman(t / half_width)
+                        })
+                        .sum();
+                    y as f32
+                })
+                .collect();
+        }
+    }
+
+    /// Resamples to `sample_rate`, keeping the duration.
+    pub fn resample(&mut self, sample_rate: f32) {
+        if sample_rate != self.sample_rate {
+            self.stretch(sample_rate / self.sample_rate);
+            self.sample_rate = sample_rate;
+        }
+    }
+}
+
+/// `sin(pi x) / (pi x)`.
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let x = std::f64::consts::PI * x;
+        x.sin() / x
+    }
+}
+
+/// Blackman window over `-1..=1`.
+fn blackman(u: f64) -> f64 {
+    let x = std::f64::consts::PI * u;
+    0.42 + 0.5 * x.cos() + 0.08 * (2.0 * x).cos()
+}
+
+/// Discrete Fourier transform of `rows * columns` points in small steps.
+///
+/// Runs the four-step algorithm: a transform of `columns` points down each of the `rows`
+/// interleaved columns, twiddle factors, then a transform of `rows` points along each row.
+/// Each step is one of the small transforms, so a long transform can be spread over time.
+struct SplitFft {
+    rows: usize,
+    columns: usize,
+    row_fft: Arc<dyn Fft<f32>>,
+    column_fft: Arc<dyn Fft<f32>>,
+    /// `W^(r k)` of row `r` and bin `k` of the columns' transforms, at `r * columns + k`.
+    twiddles: Vec<Complex<f32>>,
+    /// The columns' transforms, row `r` at `r * columns`.
+    matrix: Vec<Complex<f32>>,
+    line: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl SplitFft {
+    fn new(planner: &mut FftPlanner<f32>, len: usize, direction: FftDirection) -> Self {
+        let rows = 1 << (len.trailing_zeros() / 2);
+        let columns = len / rows;
+        let row_fft = planner.plan_fft(rows, direction);
+        let column_fft = planner.plan_fft(columns, direction);
+        let sign = match direction {
+            FftDirection::Forward => -1.0,
+            FftDirection::Inverse => 1.0,
+        };
+        let twiddles = (0..rows)
+            .flat_map(|r| (0..columns).map(move |k| (r * k) as f64))
+            .map(|rk| {
+                let phase = sign * 2.0 * std::f64::consts::PI * rk / len as f64;
+                Complex::new(phase.cos() as f32, phase.sin() as f32)
+            })
+            .collect();
+        let scratch_len = row_fft
+            .get_inplace_scratch_len()
+            .max(column_fft.get_inplace_scratch_len());
+        Self {
+            rows,
+            columns,
+            row_fft,
+            column_fft,
+            twiddles,
+            matrix: vec![Complex::default(); len],
+            line: vec![Complex::default(); rows.max(columns)],
+            scratch: vec![Complex::default(); scratch_len],
+        }
+    }
+
+    fn steps(&self) -> usize {
+        self.rows + self.columns
+    }
+
+    /// Runs the `step`th of [`steps`](SplitFft::steps), reading the `n`th input point
+    /// from `input` and handing each finished bin to `output`.
+    fn step(
+        &mut self,
+        step: usize,
+        input: impl Fn(usize) -> Complex<f32>,
+        mut output: impl FnMut(usize, Complex<f32>),
+    ) {
+        let (rows, columns) = (self.rows, self.columns);
+        if step < rows {
+            let line = &mut self.line[..columns];
+            for (m, z) in line.iter_mut().enumerate() {
+                *z = input(step + rows * m);
+            }
+            self.column_fft
+                .process_with_scratch(line, &mut self.scratch);
+            let row = step * columns..(step + 1) * columns;
+            for ((y, z), w) in self.matrix[row.clone()]
+                .iter_mut()
+                .zip(line)
+                .zip(&self.twiddles[row])
+            {
+                *y = *z * w;
+            }
+        } else {
+            let k = step - rows;
+            let line = &mut self.line[..rows];
+            for (r, z) in line.iter_mut().enumerate() {
+                *z = self.matrix[r * columns + k];
+            }
+            self.row_fft.process_with_scratch(line, &mut self.scratch);
+            for (j, z) in line.iter().enumerate() {
+                output(k + columns * j, *z);
+            }
+        }
+    }
+}
+
+/// Uniformly partitioned overlap-save convolution with one segment of an impulse response.
+///
+/// A stage whose offset leaves room for more than one block of latency spreads
+/// the transforms of each block over the following `delay` samples,
+/// so that long partitions don't load single samples.
+struct Stage {
+    size: usize,
+    offset: usize,
+    /// Samples from an input block completing to its output being ready.
+    delay: usize,
+    forward: SplitFft,
+    inverse: SplitFft,
+    /// Spectra of the segment's partitions.
+    filters: Vec<Vec<Complex<f32>>>,
+    /// Spectra of the past input frames, newest at `newest`.
+    spectra: Vec<Vec<Complex<f32>>>,
+    newest: usize,
+    /// The previous input block followed by the one being filled.
+    input: Vec<f32>,
+    filled: usize,
+    /// The frame being transformed.
+    frame: Vec<f32>,
+    accumulator: Vec<Complex<f32>>,
+    output: Vec<f32>,
+    /// Steps of the frame's convolution done so far.
+    step: usize,
+    /// Samples since the frame was taken, while it is being convolved.
+    elapsed: Option<usize>,
+}
+
+impl Stage {
+    fn new(
+        planner: &mut RealFftPlanner<f32>,
+        complex_planner: &mut FftPlanner<f32>,
+        segment: &[f32],
+        size: usize,
+        offset: usize,
+    ) -> Self {
+        let transform = planner.plan_fft_forward(2 * size);
+        let mut scratch = transform.make_scratch_vec();
+        let filters: Vec<_> = segment
+            .chunks(size)
+            .map(|partition| {
+                let mut frame = transform.make_input_vec();
+                frame[..partition.len()].copy_from_slice(partition);
+                let mut spectrum = transform.make_output_vec();
+                transform
+                    .process_with_scratch(&mut frame, &mut spectrum, &mut scratch)
+                    .unwrap();
+                spectrum
+            })
+            .collect();
+        let spectra = vec![transform.make_output_vec(); filters.len()];
+
+        Self {
+            size,
+            offset,
+            delay: offset - size,
+            forward: SplitFft::new(complex_planner, 2 * size, FftDirection::Forward),
+            inverse: SplitFft::new(complex_planner, 2 * size, FftDirection::Inverse),
+            filters,
+            spectra,
+            newest: 0,
+            input: vec![0.0; 2 * size],
+            filled: 0,
+            frame: vec![0.0; 2 * size],
+            accumulator: transform.make_output_vec(),
+            output: vec![0.0; 2 * size],
+            step: 0,
+            elapsed: None,
+        }
+    }
+
+    /// Number of bins multiplied with the filters in one step.
+    const CHUNK: usize = PARTITION;
+
+    fn steps(&self) -> usize {
+        self.forward.steps() + (self.size + 1).div_ceil(Self::CHUNK) + self.inverse.steps()
+    }
+
+    /// Feeds a sample and returns the convolved block whenever one is ready,
+    /// `delay` samples after its input block completed.
+    fn push(&mut self, x: f32) -> Option<&[f32]> {
+        self.input[self.size + self.filled] = x;
+        self.filled += 1;
+
+        let steps = self.steps();
+        let mut ready = false;
+        if let Some(elapsed) = self.elapsed {
+            // Spread evenly, finishing as the next block completes.
+            let elapsed = elapsed + 1;
+            self.run(steps * elapsed / self.delay);
+            ready = elapsed == self.delay;
+            self.elapsed = (!ready).then_some(elapsed);
+        }
+
+        if self.filled == self.size {
+            self.filled = 0;
+            self.newest = (self.newest + 1) % self.spectra.len();
+            self.frame.copy_from_slice(&self.input);
+            self.input.copy_within(self.size.., 0);
+            self.step = 0;
+            self.elapsed = Some(0);
+            if self.delay == 0 {
+                self.run(steps);
+                self.elapsed = None;
+                ready = true;
+            }
+        }
+        ready.then(|| &self.output[self.size..])
+    }
+
+    /// Runs the steps of the frame's convolution up to `target`.
+    fn run(&mut self, target: usize) {
+        let forward = self.forward.steps();
+        let multiply = forward + (self.size + 1).div_ceil(Self::CHUNK);
+        while self.step < target {
+            let step = self.step;
+            if step < forward {
+                let (frame, spectrum) = (&self.frame, &mut self.spectra[self.newest]);
+                self.forward.step(
+                    step,
+                    |n| Complex::new(frame[n], 0.0),
+                    |k, z| {
+                        if let Some(bin) = spectrum.get_mut(k) {
+                            *bin = z;
+                        }
+                    },
+                );
+            } else if step < multiply {
+                self.multiply(step - forward);
+            } else {
+                let (size, accumulator, output) = (self.size, &self.accumulator, &mut self.output);
+                let scale = 1.0 / (2 * size) as f32;
+                self.inverse.step(
+                    step - multiply,
+                    // Both halves of a real signal's spectrum mirror each other.
+                    |k| match k <= size {
+                        true => accumulator[k],
+                        false => accumulator[2 * size - k].conj(),
+                    },
+                    |n, z| output[n] = scale * z.re,
+                );
+            }
+            self.step += 1;
+        }
+    }
+
+    /// Sums the products of the filters and the spectra of the matching frames
+    /// over the `chunk`th [`CHUNK`](Stage::CHUNK) bins.
+    fn multiply(&mut self, chunk: usize) {
+        let bins = chunk * Self::CHUNK..((chunk + 1) * Self::CHUNK).min(self.size + 1);
+        let accumulator = &mut self.accumulator[bins.clone()];
+        accumulator.fill(Complex::default());
+        let count = self.spectra.len();
+        for (age, filter) in self.filters.iter().enumerate() {
+            let spectrum = &self.spectra[(self.newest + count - age) % count];
+            for ((acc, x), h) in accumulator
+                .iter_mut()
+                .zip(&spectrum[bins.clone()])
+                .zip(&filter[bins.clone()])
+            {
+                *acc += x * h;
+            }
+        }
+    }
+}
+
+/// Zero-latency partitioned convolution.
+///
+/// The first partition runs as a direct-form FIR,
+/// while the rest is convolved in the frequency domain by stages whose latency
+/// is hidden behind their offset into the impulse response.
+pub struct Convolver {
+    head: Vec<f32>,
+    history: Vec<f32>,
+    position: usize,
+    stages: Vec<Stage>,
+    /// Ring of stage outputs, indexed by time.
+    output: Vec<f32>,
+    time: usize,
+}
+
+impl Convolver {
+    pub fn new(impulse_response: &[f32], partitioning: Partitioning) -> Self {
+        let mut planner = RealFftPlanner::new();
+        let mut complex_planner = FftPlanner::new();
+        let mut stages = Vec::new();
+
+        let mut offset = PARTITION;
+        while offset < impulse_response.len() {
+            let (size, end) = match partitioning {
+                Partitioning::Uniform => (PARTITION, impulse_response.len()),
+                // Past the first stage, each starts two of its partitions into the response,
+                // leaving a partition's time to spread its transforms over.
+                Partitioning::NonUniform if offset == PARTITION => {
+                    (PARTITION, 2 * PARTITION * GROWTH)
+                }
+                Partitioning::NonUniform => (offset / 2, offset * GROWTH),
+            };
+            let end = end.min(impulse_response.len());
+            stages.push(Stage::new(
+                &mut planner,
+                &mut complex_planner,
+                &impulse_response[offset..end],
+                size,
+                offset,
+            ));
+            offset = end;
+        }
+
+        let head_len = PARTITION.min(impulse_response.len());
+        let ring = stages.iter().map(|stage| stage.offset).max().unwrap_or(0) + 1;
+        Self {
+            head: impulse_response[..head_len].to_vec(),
+            history: vec![0.0; PARTITION],
+            position: 0,
+            stages,
+            output: vec![0.0; ring],
+            time: 0,
+        }
+    }
+
+    pub fn tick(&mut self, x: f32) -> f32 {
+        self.position = (self.position + 1) % self.history.len();
+        self.history[self.position] = x;
+        let mut y = 0.0;
+        for (lag, h) in self.head.iter().enumerate() {
+            let index = (self.position + self.history.len() - lag) % self.history.len();
+            y += h * self.history[index];
+        }
+
+        let ring = self.output.len();
+        for stage in self.stages.iter_mut() {
+            let (size, offset, delay) = (stage.size, stage.offset, stage.delay);
+            if let Some(block) = stage.push(x) {
+                // The block covers the `size` inputs up to `delay` samples ago
+                // and lands `offset` samples after them.
+                let start = self.time + offset + 1 - size - delay;
+                for (n, z) in block.iter().enumerate() {
+                    self.output[(start + n) % ring] += z;
+                }
+            }
+        }
+
+        y += self.output[self.time];
+        self.output[self.time] = 0.0;
+        self.time = (self.time + 1) % ring;
+        y
+    }
+}
+
+/// Convolves each channel with its own channel of the impulse response,
+/// sharing the first one for mono responses.
+pub struct StereoConvolver {
+    channels: [Convolver; 2],
+}
+
+impl StereoConvolver {
+    pub fn new(impulse_response: &ImpulseResponse, partitioning: Partitioning) -> Self {
+        let channel = |n: usize| {
+            let channels = &impulse_response.channels;
+            Convolver::new(&channels[n.min(channels.len() - 1)], partitioning)
+        };
+        Self {
+            channels: [channel(0), channel(1)],
+        }
+    }
+
+    pub fn tick(&mut self, x: [f32; 2]) -> [f32; 2] {
+        [self.channels[0].tick(x[0]), self.channels[1].tick(x[1])]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn direct(h: &[f32], xs: &[f32]) -> Vec<f32> {
+        (0..xs.len())
+            .map(|n| {
+                h.iter()
+                    .enumerate()
+                    .filter(|(k, _)| *k <= n)
+                    .map(|(k, h)| h * xs[n - k])
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn assert_matches_direct(partitioning: Partitioning) {
+        let h: Vec<f32> = (0..5000usize)
+            .map(|n| ((n * 7919 % 113) as f32 / 56.0 - 1.0) * 0.999f32.powi(n as i32))
+            .collect();
+        let xs: Vec<f32> = (0..8000)
+            .map(|n| ((n * 31 % 17) as f32 - 8.0) / 8.0)
+            .collect();
+
+        let expected = direct(&h, &xs);
+        let mut convolver = Convolver::new(&h, partitioning);
+        for (n, (x, expected)) in xs.iter().zip(expected).enumerate() {
+            let y = convolver.tick(*x);
+            assert!((y - expected).abs() < 1e-3, "{n}: {y} != {expected}");
+        }
+    }
+
+    #[test]
+    fn uniform() {
+        assert_matches_direct(Partitioning::Uniform);
+    }
+
+    #[test]
+    fn non_uniform() {
+        assert_matches_direct(Partitioning::NonUniform);
+    }
+
+    #[test]
+    fn short_response() {
+        let mut convolver = Convolver::new(&[0.5, 0.25], Partitioning::Uniform);
+        assert_eq!(0.5, convolver.tick(1.0));
+        assert_eq!(0.25, convolver.tick(0.0));
+        assert_eq!(0.0, convolver.tick(0.0));
+    }
+
+    #[test]
+    fn edit() {
+        let mut ir = ImpulseResponse {
+            channels: vec![vec![1.0, 2.0, 3.0, 4.0]],
+            sample_rate: 48000.0,
+        };
+        ir.trim(1, 2);
+        assert_eq!(vec![2.0, 3.0], ir.channels[0]);
+        ir.reverse();
+        assert_eq!(vec![3.0, 2.0], ir.channels[0]);
+        ir.stretch(2.0);
+        assert_eq!(4, ir.len());
+        // The original samples stay in place.
+        assert!((ir.channels[0][0] - 3.0).abs() < 1e-6);
+        assert!((ir.channels[0][2] - 2.0).abs() < 1e-6);
+        ir.resample(24000.0);
+        assert_eq!(2, ir.len());
+        assert_eq!(24000.0, ir.sample_rate);
+    }
+
+    #[test]
+    fn resample() {
+        let tone = |frequency: f64, sample_rate: f64, len: usize| -> Vec<f32> {
+            (0..len)
+                .map(|n| {
+                    (2.0 * std::f64::consts::PI * frequency * n as f64 / sample_rate).sin() as f32
+                })
+                .collect()
+        };
+        let mut ir = ImpulseResponse {
+            channels: vec![tone(1000.0, 48000.0, 4800), tone(20000.0, 48000.0, 4800)],
+            sample_rate: 48000.0,
+        };
+        ir.resample(22050.0);
+        assert_eq!(2205, ir.len());
+
+        // Away from the ends, which the sinc reaches past.
+        let inner = 100..ir.len() - 100;
+        let expected = tone(1000.0, 22050.0, ir.len());
+        for (y, expected) in ir.channels[0][inner.clone()]
+            .iter()
+            .zip(&expected[inner.clone()])
+        {
+            assert!((y - expected).abs() < 1e-3, "{y} != {expected}");
+        }
+        // Filtered out instead of folding down to 2050 Hz.
+        assert!(ir.channels[1][inner].iter().all(|y| y.abs() < 1e-3));
+    }
+
+    #[test]
+    fn split_fft() {
+        let mut planner = FftPlanner::new();
+        for len in [128, 1024, 8192] {
+            let input: Vec<_> = (0..len)
+                .map(|n| Complex::new((n * 7919 % 113) as f32 / 56.0 - 1.0, (n % 7) as f32))
+                .collect();
+            for direction in [FftDirection::Forward, FftDirection::Inverse] {
+                let mut expected = input.clone();
+                planner.plan_fft(len, direction).process(&mut expected);
+                let mut fft = SplitFft::new(&mut planner, len, direction);
+                let mut actual = vec![Complex::default(); len];
+                for step in 0..fft.steps() {
+                    fft.step(step, |n| input[n], |k, z| actual[k] = z);
+                }
+                for (x, y) in expected.iter().zip(&actual) {
+                    assert!((x - y).norm() < 1e-5 * len as f32, "{len}: {x} != {y}");
+                }
+            }
+        }
+    }
+}
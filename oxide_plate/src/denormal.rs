@@ -0,0 +1,97 @@
+//! Flushing of denormal floats on the audio thread.
+//!
+//! Decaying tails pass through denormal values, which are many times slower to compute with
+//! on most CPUs. While a [`FlushToZero`] guard is alive, the FPU treats them as zero
+//! both when reading (DAZ) and when writing (FTZ).
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod control {
+    use core::arch::asm;
+
+    pub type Register = u32;
+
+    /// Flush-to-zero (bit 15) and denormals-are-zero (bit 6) of MXCSR.
+    pub const FLAGS: Register = 0x8040;
+
+    pub fn read() -> Register {
+        let mut csr: Register = 0;
+        unsafe { asm!("stmxcsr [{}]", in(reg) &mut csr, options(nostack, preserves_flags)) };
+        csr
+    }
+
+    pub fn write(csr: Register) {
+        unsafe { asm!("ldmxcsr [{}]", in(reg) &csr, options(nostack, readonly, preserves_flags)) };
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod control {
+    use core::arch::asm;
+
+    pub type Register = u64;
+
+    /// Flush-to-zero (bit 24) of FPCR, which covers inputs as well.
+    pub const FLAGS: Register = 1 << 24;
+
+    pub fn read() -> Register {
+        let fpcr: Register;
+        unsafe { asm!("mrs {}, fpcr", out(reg) fpcr, options(nomem, nostack, preserves_flags)) };
+        fpcr
+    }
+
+    pub fn write(fpcr: Register) {
+        unsafe { asm!("msr fpcr, {}", in(reg) fpcr, options(nomem, nostack, preserves_flags)) };
+    }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+mod control {
+    pub type Register = ();
+
+    pub const FLAGS: Register = ();
+
+    pub fn read() -> Register {}
+
+    pub fn write(_: Register) {}
+}
+
+/// Enables flushing of denormals on the current thread until dropped,
+/// then restores the previous mode.
+#[must_use]
+pub struct FlushToZero {
+    previous: control::Register,
+}
+
+impl FlushToZero {
+    pub fn enable() -> Self {
+        let previous = control::read();
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+        control::write(previous | control::FLAGS);
+        Self { previous }
+    }
+}
+
+impl Drop for FlushToZero {
+    fn drop(&mut self) {
+        control::write(self.previous);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hint::black_box;
+
+    #[test]
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64"))]
+    fn flush() {
+        let denormal = black_box(f32::MIN_POSITIVE / 4.0);
+        assert_ne!(0.0, black_box(denormal * 2.0));
+        {
+            let _guard = FlushToZero::enable();
+            assert_eq!(0.0, black_box(denormal * 2.0));
+            assert_eq!(0.0, black_box(f32::MIN_POSITIVE) * black_box(0.25));
+        }
+        assert_ne!(0.0, black_box(denormal * 2.0));
+    }
+}
@@ -0,0 +1,475 @@
+//! The plugin's graphical editor.
+//!
+//! What is drawn is decided by plain functions ([`sections`], [`decay_curve`], [`meter_fraction`])
+//! so that the layout can be tested without a display.
+
+use crate::{
+    analyzer::{bands, Analyzer, SampleRing, Waterfall, FFT_SIZE},
+    meter::{Level, Meters},
+    plate::{DECAY_DIFFUSION_1_1, DECAY_DIFFUSION_1_2, DECAY_DIFFUSION_2_1, DECAY_DIFFUSION_2_2},
+    plate::{DELAY_1, DELAY_2, DELAY_3, DELAY_4},
+    preset::{self, factory_bank, Preset},
+    PlatePluginParams,
+};
+use nih_plug::prelude::*;
+use nih_plug_egui::{
+    create_egui_editor,
+    egui::{self, Color32, Pos2, Rect, Sense, Shape, Stroke},
+    widgets::ParamSlider,
+    EguiState,
+};
+use std::{
+    path::PathBuf,
+    sync::{atomic::Ordering, Arc},
+};
+
+pub const WIDTH: u32 = 760;
+pub const HEIGHT: u32 = 520;
+
+/// Lowest level shown by the meters and the decay curve.
+pub const FLOOR_DB: f32 = -60.0;
+
+/// Length of the time axis of the decay curve in seconds.
+pub const DECAY_VIEW: f32 = 10.0;
+
+pub fn default_state() -> Arc<EguiState> {
+    EguiState::from_size(WIDTH, HEIGHT)
+}
+
+/// A parameter and the widget it is drawn with.
+#[derive(Clone, Copy)]
+pub enum Control<'a> {
+    Float(&'a FloatParam),
+    Int(&'a IntParam),
+    Bool(&'a BoolParam),
+    /// Drawn as a button that holds the parameter on while pressed.
+    Momentary(&'a BoolParam),
+    Engine(&'a EnumParam<crate::Engine>),
+    Mode(&'a EnumParam<crate::Mode>),
+    Safety(&'a EnumParam<crate::Safety>),
+    Precision(&'a EnumParam<crate::Precision>),
+    Oversampling(&'a EnumParam<crate::Oversampling>),
+    Partitioning(&'a EnumParam<crate::convolution::Partitioning>),
+}
+
+impl Control<'_> {
+    pub fn name(&self) -> &str {
+        match self {
+            Control::Float(param) => param.name(),
+            Control::Int(param) => param.name(),
+            Control::Bool(param) => param.name(),
+            Control::Momentary(param) => param.name(),
+            Control::Engine(param) => param.name(),
+            Control::Mode(param) => param.name(),
+            Control::Safety(param) => param.name(),
+            Control::Precision(param) => param.name(),
+            Control::Oversampling(param) => param.name(),
+            Control::Partitioning(param) => param.name(),
+        }
+    }
+
+    fn show(&self, ui: &mut egui::Ui, setter: &ParamSetter) {
+        ui.label(self.name());
+        match self {
+            Control::Float(param) => ui.add(ParamSlider::for_param(*param, setter)),
+            Control::Int(param) => ui.add(ParamSlider::for_param(*param, setter)),
+            Control::Bool(param) => ui.add(ParamSlider::for_param(*param, setter)),
+            Control::Momentary(param) => {
+                let response = ui.add(egui::SelectableLabel::new(param.value(), "Hold"));
+                let held = response.is_pointer_button_down_on();
+                if held != param.value() {
+                    setter.begin_set_parameter(*param);
+                    setter.set_parameter(*param, held);
+                    setter.end_set_parameter(*param);
+                }
+                response
+            }
+            Control::Engine(param) => ui.add(ParamSlider::for_param(*param, setter)),
+            Control::Mode(param) => ui.add(ParamSlider::for_param(*param, setter)),
+            Control::Safety(param) => ui.add(ParamSlider::for_param(*param, setter)),
+            Control::Precision(param) => ui.add(ParamSlider::for_param(*param, setter)),
+            Control::Oversampling(param) => ui.add(ParamSlider::for_param(*param, setter)),
+            Control::Partitioning(param) => ui.add(ParamSlider::for_param(*param, setter)),
+        };
+    }
+}
+
+/// A titled group of controls.
+pub struct Section<'a> {
+    pub title: &'static str,
+    pub controls: Vec<Control<'a>>,
+}
+
+/// Groups the parameters in the order they are drawn.
+pub fn sections(params: &PlatePluginParams) -> [Section<'_>; 4] {
+    use Control::*;
+    [
+        Section {
+            title: "Input",
+            controls: vec![
+                Int(&params.predelay),
+                Float(&params.bandwidth),
+                Float(&params.input_diffusion_1),
+                Float(&params.input_diffusion_2),
+            ],
+        },
+        Section {
+            title: "Tank",
+            controls: vec![
+                Float(&params.decay),
+                Float(&params.damping),
+                Float(&params.drive),
+                Float(&params.character),
+                Float(&params.decay_diffusion_1),
+                Float(&params.decay_diffusion_2),
+                Int(&params.decay_mod),
+                Float(&params.shimmer),
+                Int(&params.shimmer_interval),
+            ],
+        },
+        Section {
+            title: "Output",
+            controls: vec![
+                Bool(&params.bypass),
+                Bool(&params.spillover),
+                Engine(&params.engine),
+                Mode(&params.mode),
+                Float(&params.wet),
+                Float(&params.send_level),
+                Momentary(&params.wet_solo),
+                Float(&params.output_gain),
+                Safety(&params.safety),
+                Float(&params.meter_decay),
+                Precision(&params.precision),
+                Oversampling(&params.oversampling),
+                Bool(&params.reverse),
+                Float(&params.reverse_length),
+                Bool(&params.reverse_sync),
+                Float(&params.reverse_beats),
+            ],
+        },
+        Section {
+            title: "Impulse response",
+            controls: vec![
+                Float(&params.ir_start),
+                Float(&params.ir_length),
+                Float(&params.ir_stretch),
+                Bool(&params.ir_reverse),
+                Partitioning(&params.ir_partitioning),
+            ],
+        },
+    ]
+}
+
+/// Maps a level in dBFS to the filled fraction of a meter.
+pub fn meter_fraction(db: f32) -> f32 {
+    ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0)
+}
+
+/// Estimated decay of the tank as `(seconds, dB)` points,
+/// for low frequencies and for frequencies near Nyquist.
+///
+/// Each pass through one half of the tank applies `decay` twice
+/// and the damping filter once, whose gain is 1 at DC
+/// and `(1 - damping) / (1 + damping)` at Nyquist.
+pub fn decay_curve(
+    decay: f32,
+    damping: f32,
+    sample_rate: f32,
+    points: usize,
+) -> [Vec<(f32, f32)>; 2] {
+    let pass = tank_pass(sample_rate);
+    let nyquist = (1.0 - damping) / (1.0 + damping);
+    [1.0, nyquist].map(|filter| {
+        let db_per_second = util::gain_to_db(decay * decay * filter) / pass;
+        (0..points)
+            .map(|i| {
+                let t = DECAY_VIEW * i as f32 / (points - 1).max(1) as f32;
+                (t, (db_per_second * t).max(FLOOR_DB))
+            })
+            .collect()
+    })
+}
+
+/// Time for the level at low frequencies to fall by 60 dB.
+pub fn rt60(decay: f32, sample_rate: f32) -> f32 {
+    -60.0 * tank_pass(sample_rate) / util::gain_to_db(decay * decay)
+}
+
+/// Average duration of a pass through one half of the tank in seconds.
+fn tank_pass(sample_rate: f32) -> f32 {
+    let half_1 = DECAY_DIFFUSION_1_1 + DELAY_1 + DECAY_DIFFUSION_2_1 + DELAY_2;
+    let half_2 = DECAY_DIFFUSION_1_2 + DELAY_3 + DECAY_DIFFUSION_2_2 + DELAY_4;
+    (half_1 + half_2) as f32 / 2.0 / sample_rate
+}
+
+struct EditorState {
+    bank: Vec<Preset>,
+    ir_path: String,
+    analyzer: Analyzer,
+    waterfall: Waterfall,
+    frame: Vec<f32>,
+    spectrum: Vec<f32>,
+    /// Samples pushed to the ring when the last spectrum was taken.
+    analyzed: usize,
+}
+
+/// Samples between two spectra of the waterfall.
+const HOP: usize = FFT_SIZE / 2;
+
+/// Number of bands the tail view is drawn with.
+const BANDS: usize = 64;
+
+impl EditorState {
+    /// Takes a spectrum of the latest wet samples once enough new ones arrived.
+    fn analyze(&mut self, ring: &SampleRing) {
+        let written = ring.read_latest(&mut self.frame);
+        if written.wrapping_sub(self.analyzed) < HOP {
+            return;
+        }
+        self.analyzed = written;
+        self.analyzer.spectrum(&self.frame, &mut self.spectrum);
+        self.waterfall.push(&self.spectrum);
+    }
+}
+
+pub fn create(
+    params: Arc<PlatePluginParams>,
+    meters: Arc<Meters>,
+    analysis: Arc<SampleRing>,
+) -> Option<Box<dyn Editor>> {
+    let ir_path = params
+        .ir_path
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+    let analyzer = Analyzer::new(FFT_SIZE);
+    let state = EditorState {
+        bank: factory_bank(),
+        ir_path,
+        spectrum: vec![0.0; analyzer.bins()],
+        frame: vec![0.0; FFT_SIZE],
+        analyzer,
+        waterfall: Waterfall::default(),
+        analyzed: 0,
+    };
+    create_egui_editor(
+        params.editor_state.clone(),
+        state,
+        |_, _| {},
+        move |ctx, setter, state| {
+            egui::TopBottomPanel::top("header").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("oxide plate");
+                    presets(ui, setter, &params, &state.bank);
+                });
+            });
+            egui::SidePanel::right("meters").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    meter(ui, "In", &meters.input);
+                    meter(ui, "Out", &meters.output);
+                });
+            });
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let sample_rate = meters.sample_rate.load(Ordering::Relaxed);
+                state.analyze(&analysis);
+                ui.horizontal(|ui| {
+                    decay_view(ui, &params, sample_rate);
+                    tail_view(ui, state, sample_rate);
+                });
+                ui.horizontal_top(|ui| {
+                    for section in sections(&params) {
+                        ui.group(|ui| {
+                            ui.vertical(|ui| {
+                                ui.strong(section.title);
+                                for control in section.controls {
+                                    control.show(ui, setter);
+                                }
+                                if section.title == "Impulse response" {
+                                    impulse_response(ui, &params, &mut state.ir_path);
+                                }
+                            });
+                        });
+                    }
+                });
+            });
+        },
+    )
+}
+
+fn presets(ui: &mut egui::Ui, setter: &ParamSetter, params: &PlatePluginParams, bank: &[Preset]) {
+    egui::ComboBox::from_label("Preset")
+        .selected_text("Factory")
+        .show_ui(ui, |ui| {
+            for preset in bank {
+                if ui.selectable_label(false, &preset.name).clicked() {
+                    preset::apply(preset, params, setter.raw_context);
+                }
+            }
+        });
+}
+
+fn impulse_response(ui: &mut egui::Ui, params: &PlatePluginParams, path: &mut String) {
+    ui.label("File");
+    ui.text_edit_singleline(path);
+    if ui.button("Load").clicked() {
+        let path = (!path.is_empty()).then(|| PathBuf::from(path.as_str()));
+        *params.ir_path.lock().unwrap() = path;
+        params.ir_changed.store(true, Ordering::Relaxed);
+    }
+}
+
+fn decay_view(ui: &mut egui::Ui, params: &PlatePluginParams, sample_rate: f32) {
+    let decay = params.decay.value();
+    let damping = params.damping.value();
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(ui.available_width() / 2.0, 140.0),
+        Sense::hover(),
+    );
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, Color32::from_gray(24));
+
+    let to_screen = |(t, db): (f32, f32)| {
+        Pos2::new(
+            rect.left() + rect.width() * t / DECAY_VIEW,
+            rect.top() + rect.height() * db / FLOOR_DB,
+        )
+    };
+    let colors = [Color32::LIGHT_BLUE, Color32::from_rgb(240, 160, 80)];
+    for (curve, color) in decay_curve(decay, damping, sample_rate, 128)
+        .into_iter()
+        .zip(colors)
+    {
+        let points = curve.into_iter().map(to_screen).collect();
+        painter.add(Shape::line(points, Stroke::new(1.5, color)));
+    }
+    painter.text(
+        rect.right_top() + egui::vec2(-8.0, 8.0),
+        egui::Align2::RIGHT_TOP,
+        format!("RT60 {:.2} s", rt60(decay, sample_rate)),
+        egui::FontId::proportional(12.0),
+        Color32::GRAY,
+    );
+}
+
+/// Draws the energy decay relief of the wet signal as a waterfall,
+/// the oldest frame at the back, with the latest spectrum in front.
+fn tail_view(ui: &mut egui::Ui, state: &EditorState, sample_rate: f32) {
+    let (response, painter) =
+        ui.allocate_painter(egui::vec2(ui.available_width(), 140.0), Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, Color32::from_gray(24));
+
+    let relief = state.waterfall.energy_decay_relief();
+    let depth = relief.len().max(1) as f32;
+    let nyquist = sample_rate / 2.0;
+    let floor = crate::analyzer::FLOOR_DB / 2.0;
+    let to_screen = |(f, db): (f32, f32), back: f32| {
+        let x = (f / 20.0).ln() / (nyquist / 20.0).ln();
+        let y = (db / floor).clamp(0.0, 1.0);
+        Pos2::new(
+            rect.left() + rect.width() * (0.8 * x + 0.2 * back),
+            rect.top() + rect.height() * (0.3 * (1.0 - back) + 0.7 * y),
+        )
+    };
+    for (i, frame) in relief.iter().enumerate().rev() {
+        let back = 1.0 - i as f32 / depth;
+        let points = bands(frame, sample_rate, BANDS)
+            .into_iter()
+            .map(|band| to_screen(band, back))
+            .collect();
+        let color = Color32::from_rgb(80, 140, 220).gamma_multiply(1.0 - 0.8 * back);
+        painter.add(Shape::line(points, Stroke::new(1.0, color)));
+    }
+    let points = bands(&state.spectrum, sample_rate, BANDS)
+        .into_iter()
+        .map(|band| to_screen(band, 0.0))
+        .collect();
+    painter.add(Shape::line(points, Stroke::new(1.5, Color32::WHITE)));
+}
+
+/// Draws the RMS level as a bar and the peak level as a line,
+/// under a clip indicator that is reset by clicking it.
+fn meter(ui: &mut egui::Ui, label: &str, level: &Level) {
+    ui.vertical(|ui| {
+        let clipped = level.clip.load(Ordering::Relaxed);
+        let (clip, painter) = ui.allocate_painter(egui::vec2(14.0, 8.0), Sense::click());
+        let color = if clipped {
+            Color32::RED
+        } else {
+            Color32::from_gray(48)
+        };
+        painter.rect_filled(clip.rect, 2.0, color);
+        if clip.clicked() {
+            level.clip.store(false, Ordering::Relaxed);
+        }
+
+        let (response, painter) = ui.allocate_painter(egui::vec2(14.0, 240.0), Sense::hover());
+        let rect = response.rect;
+        let y = |db| rect.bottom() - rect.height() * meter_fraction(db);
+        painter.rect_filled(rect, 2.0, Color32::from_gray(24));
+
+        let rms = level.rms.load(Ordering::Relaxed);
+        let bar = Rect::from_min_max(Pos2::new(rect.left(), y(rms)), rect.right_bottom());
+        painter.rect_filled(bar, 2.0, Color32::from_rgb(100, 200, 120));
+
+        let peak = level.peak.load(Ordering::Relaxed);
+        if peak > FLOOR_DB {
+            let y = y(peak);
+            painter.line_segment(
+                [Pos2::new(rect.left(), y), Pos2::new(rect.right(), y)],
+                Stroke::new(2.0, Color32::WHITE),
+            );
+        }
+        ui.label(label);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_param_is_shown_once() {
+        let params = PlatePluginParams::default();
+        let mut shown: Vec<String> = sections(&params)
+            .iter()
+            .flat_map(|section| section.controls.iter().map(|c| c.name().to_owned()))
+            .collect();
+        let mut names: Vec<String> = params
+            .param_map()
+            .into_iter()
+            .map(|(_, ptr, _)| unsafe { ptr.name() }.to_owned())
+            .collect();
+        shown.sort();
+        names.sort();
+        assert_eq!(names, shown);
+    }
+
+    #[test]
+    fn decay() {
+        let [low, high] = decay_curve(0.5, 0.5, 44100.0, 64);
+        assert_eq!(64, low.len());
+        assert_eq!((0.0, 0.0), low[0]);
+        assert_eq!(DECAY_VIEW, low[63].0);
+        for (low, high) in low.iter().zip(&high) {
+            assert!(high.1 <= low.1);
+        }
+        assert!(low.windows(2).all(|w| w[1].1 <= w[0].1));
+        assert!(low.iter().all(|(_, db)| *db >= FLOOR_DB));
+
+        let t = rt60(0.95, 44100.0);
+        let [low, _] = decay_curve(0.95, 0.0, 44100.0, 2);
+        let db_per_second = low[1].1 / DECAY_VIEW;
+        assert!((db_per_second * t + 60.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn meters() {
+        assert_eq!(0.0, meter_fraction(util::MINUS_INFINITY_DB));
+        assert_eq!(0.5, meter_fraction(FLOOR_DB / 2.0));
+        assert_eq!(1.0, meter_fraction(6.0));
+    }
+}
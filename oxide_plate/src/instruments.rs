@@ -1,6 +0,0 @@
-mod delay;
-mod filters;
-
-pub use delay::Delay;
-pub use filters::APF;
-pub use filters::IIR;
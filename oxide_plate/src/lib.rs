@@ -1,17 +1,190 @@
+use analyzer::{SampleRing, FFT_SIZE};
+use convolution::*;
+use denormal::FlushToZero;
+use instruments::{soft_clip, Downsampler, Limiter, Upsampler, PHASE_TAPS};
+use meter::{Ballistics, Meters};
+use migration::*;
 use nih_plug::prelude::*;
+use nih_plug::wrapper::state::PluginState;
+use nih_plug_egui::EguiState;
 use plate::*;
-use std::{marker::PhantomData, ops::Deref, sync::Arc};
+use std::{
+    ops::Deref,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
 
-mod instruments;
-mod plate;
+mod analyzer;
+pub mod convolution;
+mod denormal;
+mod editor;
+mod meter;
+pub mod migration;
+pub mod preset;
+
+pub use oxide_plate_dsp::{instruments, plate};
+
+/// Longest pre delay in samples.
+const MAX_PREDELAY: usize = 4095;
+
+/// Highest oversampling factor, which the plates' buffers are allocated for.
+const MAX_OVERSAMPLING: usize = 4;
+
+/// Delay lengths of a plate oversampled by [`MAX_OVERSAMPLING`].
+const MAX_LENGTHS: PlateLengths = PlateLengths::new(MAX_OVERSAMPLING as f64 * SAMPLE_RATE);
+
+/// Level the safety stage keeps the wet signal under.
+const CEILING: f32 = 1.0;
+
+/// Level above which the soft clipper starts bending the wet signal.
+const SOFT_CLIP_KNEE: f32 = 0.5;
+
+/// Highest gain into the tank's saturation.
+const MAX_DRIVE: f32 = 16.0;
+
+/// Time constant of the limiter's release in seconds.
+const LIMITER_RELEASE: f32 = 0.1;
+
+/// Length of the crossfade between the processed and the dry signal on bypass, in seconds.
+const BYPASS_RAMP: f32 = 0.02;
+
+/// Default time in seconds for the meters' peak to fall by 60 dB.
+const METER_DECAY: f32 = 1.5;
+
+/// Step in samples the reverse window snaps to, so that the latency it adds
+/// doesn't change with every slight move of the host's tempo.
+const REVERSE_WINDOW_STEP: usize = 256;
+
+/// Longest latency reported to the host, which the dry signal is delayed by at most.
+const MAX_LATENCY: usize = REVERSE + PHASE_TAPS;
+
+/// The single precision plate, with the tank halves in SIMD lanes where enabled.
+#[cfg(feature = "simd")]
+type PluginPlate = OwnedSimdPlate;
+#[cfg(not(feature = "simd"))]
+type PluginPlate = OwnedPlate<f32>;
+
+/// Allocates the single precision plate for every oversampling factor.
+#[cfg(feature = "simd")]
+fn plugin_plate() -> PluginPlate {
+    let buffers = SimdPlateBuffers::new(&MAX_LENGTHS, MAX_OVERSAMPLING * MAX_PREDELAY);
+    OwnedSimdPlate::new(buffers, PlateLengths::default())
+}
+
+/// Allocates the single precision plate for every oversampling factor.
+#[cfg(not(feature = "simd"))]
+fn plugin_plate() -> PluginPlate {
+    let arena = PlateArena::new(&MAX_LENGTHS, MAX_OVERSAMPLING * MAX_PREDELAY);
+    OwnedPlate::new(arena, PlateLengths::default())
+}
 
 struct PlatePlugin {
     params: Arc<PlatePluginParams>,
-    plate: Plate<'static, f32>,
+    /// Owns its buffers, sized for every oversampling factor and freed along with the plugin.
+    plate: PluginPlate,
+    /// Runs instead of `plate` at [`Precision::Double`].
+    plate_f64: OwnedPlate<f64>,
+    /// Precision of the previous buffer, to clear the plate switched to.
+    precision: Precision,
+    /// Raise each channel to the plate's rate.
+    upsamplers: [Upsampler<'static, f32>; 2],
+    /// Bring each channel back to the host's rate.
+    downsamplers: [Downsampler<'static, f32>; 2],
+    convolver: Option<StereoConvolver>,
+    loader: Arc<ImpulseResponseLoader>,
+    meters: Arc<Meters>,
+    /// Wet signal for the editor's analyzer.
+    analysis: Arc<SampleRing>,
+    input_meter: Ballistics,
+    output_meter: Ballistics,
+    limiter: Limiter<f32>,
+    /// Position of the bypass crossfade, from 0 when active to 1 when bypassed.
+    bypass_fade: f32,
+    sample_rate: f32,
+    tempo: Option<f64>,
+    /// Latency reported to the host.
+    latency: u32,
+    /// Keeps the dry signal aligned with the wet one, which the host shifts back by `latency`.
+    dry_delay: DryDelay,
+}
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Engine {
+    Plate,
+    Convolution,
+}
+
+/// How the plugin is inserted into the mix.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// On a channel, mixing dry and wet by the wet parameter.
+    Insert,
+    /// On a send bus, outputting only the wet signal at the send level.
+    Send,
+}
+
+/// Protection of the wet signal against runaway levels.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Safety {
+    Off,
+    #[name = "Soft clip"]
+    SoftClip,
+    Limiter,
+}
+
+/// Sample format the plate computes in, converting from and to the host's `f32` at the edges.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Precision {
+    #[name = "32 bit"]
+    Single,
+    /// Keeps rounding in the tank from adding noise to long tails, at about twice the memory.
+    #[name = "64 bit"]
+    Double,
+}
+
+/// Factor the plate's sample rate is raised by, so that the modulated all-passes don't alias.
+///
+/// The plate's delays scale along, so it sounds the same apart from the reverse window,
+/// which is limited to [`REVERSE`] samples at the raised rate.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Oversampling {
+    Off,
+    #[name = "2x"]
+    X2,
+    #[name = "4x"]
+    X4,
+}
+
+impl Oversampling {
+    fn factor(self) -> usize {
+        match self {
+            Oversampling::Off => 1,
+            Oversampling::X2 => 2,
+            Oversampling::X4 => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Task {
+    LoadImpulseResponse,
 }
 
 #[derive(Params, Debug)]
 struct PlatePluginParams {
+    #[persist = "editor_state"]
+    pub editor_state: Arc<EguiState>,
+    #[id = "bypass"]
+    pub bypass: BoolParam,
+    #[id = "spillover"]
+    pub spillover: BoolParam,
+    #[id = "engine"]
+    pub engine: EnumParam<Engine>,
+    #[persist = "state_version"]
+    pub state_version: Mutex<u32>,
     #[id = "predelay"]
     pub predelay: IntParam,
     #[id = "bandwidth"]
@@ -28,45 +201,162 @@ struct PlatePluginParams {
     pub damping: FloatParam,
     #[id = "decay"]
     pub decay: FloatParam,
+    #[id = "drive"]
+    pub drive: FloatParam,
+    #[id = "character"]
+    pub character: FloatParam,
+    #[id = "mode"]
+    pub mode: EnumParam<Mode>,
     #[id = "wet"]
     pub wet: FloatParam,
+    #[id = "send_level"]
+    pub send_level: FloatParam,
+    /// Mutes the dry signal in insert mode while held.
+    #[id = "wet_solo"]
+    pub wet_solo: BoolParam,
+    #[id = "output_gain"]
+    pub output_gain: FloatParam,
+    #[id = "safety"]
+    pub safety: EnumParam<Safety>,
+    #[id = "meter_decay"]
+    pub meter_decay: FloatParam,
+    #[id = "precision"]
+    pub precision: EnumParam<Precision>,
+    #[id = "oversampling"]
+    pub oversampling: EnumParam<Oversampling>,
     #[id = "decay_mod"]
     pub decay_mod: IntParam,
+    #[id = "shimmer"]
+    pub shimmer: FloatParam,
+    #[id = "shimmer_interval"]
+    pub shimmer_interval: IntParam,
+    #[id = "reverse"]
+    pub reverse: BoolParam,
+    #[id = "reverse_length"]
+    pub reverse_length: FloatParam,
+    #[id = "reverse_sync"]
+    pub reverse_sync: BoolParam,
+    #[id = "reverse_beats"]
+    pub reverse_beats: FloatParam,
+    #[persist = "ir_path"]
+    pub ir_path: Mutex<Option<PathBuf>>,
+    #[id = "ir_start"]
+    pub ir_start: FloatParam,
+    #[id = "ir_length"]
+    pub ir_length: FloatParam,
+    #[id = "ir_stretch"]
+    pub ir_stretch: FloatParam,
+    #[id = "ir_reverse"]
+    pub ir_reverse: BoolParam,
+    #[id = "ir_partitioning"]
+    pub ir_partitioning: EnumParam<Partitioning>,
+    /// Set when the impulse response has to be reloaded.
+    pub ir_changed: Arc<AtomicBool>,
+}
+
+/// Hands impulse responses loaded by the background task over to the audio thread.
+#[derive(Default)]
+struct ImpulseResponseLoader {
+    sample_rate: AtomicF32,
+    /// The newly loaded convolver, or the replaced one waiting to be dropped off the audio thread.
+    slot: Mutex<Option<StereoConvolver>>,
+    ready: AtomicBool,
+}
+
+impl ImpulseResponseLoader {
+    fn load(&self, params: &PlatePluginParams) {
+        let Some(path) = params.ir_path.lock().unwrap().clone() else {
+            return;
+        };
+        let mut impulse_response = match ImpulseResponse::load(&path) {
+            Ok(impulse_response) if !impulse_response.is_empty() => impulse_response,
+            Ok(_) => {
+                nih_error!("{} has no samples", path.display());
+                return;
+            }
+            Err(err) => {
+                nih_error!("failed to load {}: {}", path.display(), err);
+                return;
+            }
+        };
+
+        let samples_per_ms = impulse_response.sample_rate / 1000.0;
+        impulse_response.trim(
+            (params.ir_start.value() * samples_per_ms) as usize,
+            (params.ir_length.value() * samples_per_ms) as usize,
+        );
+        if params.ir_reverse.value() {
+            impulse_response.reverse();
+        }
+        impulse_response.stretch(params.ir_stretch.value());
+        impulse_response.resample(self.sample_rate.load(Ordering::Relaxed));
+
+        let convolver = StereoConvolver::new(&impulse_response, params.ir_partitioning.value());
+        *self.slot.lock().unwrap() = Some(convolver);
+        self.ready.store(true, Ordering::Release);
+    }
+
+    /// Swaps in a newly loaded convolver without blocking or deallocating.
+    fn receive(&self, convolver: &mut Option<StereoConvolver>) {
+        if !self.ready.load(Ordering::Acquire) {
+            return;
+        }
+        if let Ok(mut slot) = self.slot.try_lock() {
+            std::mem::swap(&mut *slot, convolver);
+            self.ready.store(false, Ordering::Release);
+        }
+    }
 }
 
 impl Default for PlatePlugin {
     fn default() -> Self {
-        let buffers = Box::new(PlateBuffers {
-            predelay: vec![0.0; 4096],
-            input_diffusion_1_1: vec![0.0; INPUT_DIFFUSION_1_1 + 1],
-            input_diffusion_1_2: vec![0.0; INPUT_DIFFUSION_1_2 + 1],
-            input_diffusion_2_1: vec![0.0; INPUT_DIFFUSION_2_1 + 1],
-            input_diffusion_2_2: vec![0.0; INPUT_DIFFUSION_2_2 + 1],
-            decay_diffusion_1_1: vec![0.0; DECAY_DIFFUSION_1_1 + EXCURSION + 1],
-            decay_diffusion_1_2: vec![0.0; DECAY_DIFFUSION_1_2 + EXCURSION + 1],
-            decay_diffusion_2_1: vec![0.0; DECAY_DIFFUSION_2_1 + 1],
-            decay_diffusion_2_2: vec![0.0; DECAY_DIFFUSION_2_2 + 1],
-            delay_1: vec![0.0; DELAY_1 + 1],
-            delay_2: vec![0.0; DELAY_2 + 1],
-            delay_3: vec![0.0; DELAY_3 + 1],
-            delay_4: vec![0.0; DELAY_4 + 1],
-            prefilter: vec![0.0],
-            dumping_1: vec![0.0],
-            dumping_2: vec![0.0],
-            tank: vec![0.0, 0.0],
-            _t: PhantomData,
-        });
         Self {
             params: Arc::new(PlatePluginParams::default()),
-            plate: Box::leak(buffers).build(),
+            plate: plugin_plate(),
+            plate_f64: OwnedPlate::new(
+                PlateArena::new(&MAX_LENGTHS, MAX_OVERSAMPLING * MAX_PREDELAY),
+                PlateLengths::default(),
+            ),
+            precision: Precision::Single,
+            upsamplers: [(); 2].map(|_| Upsampler::allocate(1)),
+            downsamplers: [(); 2].map(|_| Downsampler::allocate(MAX_OVERSAMPLING, 1)),
+            convolver: None,
+            loader: Arc::new(ImpulseResponseLoader::default()),
+            meters: Arc::new(Meters::default()),
+            analysis: Arc::new(SampleRing::new(2 * FFT_SIZE)),
+            input_meter: Ballistics::new(METER_DECAY, 44100.0),
+            output_meter: Ballistics::new(METER_DECAY, 44100.0),
+            limiter: Limiter::new(CEILING, release(LIMITER_RELEASE, 44100.0)),
+            bypass_fade: 0.0,
+            sample_rate: 44100.0,
+            tempo: None,
+            latency: 0,
+            dry_delay: DryDelay::new(MAX_LATENCY),
         }
     }
 }
 
 impl Default for PlatePluginParams {
     fn default() -> Self {
+        let ir_changed = Arc::new(AtomicBool::new(false));
+        let reload = {
+            let ir_changed = ir_changed.clone();
+            move || ir_changed.store(true, Ordering::Relaxed)
+        };
         Self {
-            predelay: IntParam::new("Pre delay", 50, IntRange::Linear { min: 1, max: 4095 }),
+            editor_state: editor::default_state(),
+            bypass: BoolParam::new("Bypass", false).make_bypass(),
+            spillover: BoolParam::new("Spillover", true),
+            engine: EnumParam::new("Engine", Engine::Plate),
+            state_version: Mutex::new(STATE_VERSION),
+            predelay: IntParam::new(
+                "Pre delay",
+                50,
+                IntRange::Linear {
+                    min: 1,
+                    max: MAX_PREDELAY as i32,
+                },
+            ),
             bandwidth: FloatParam::new(
                 "Bandwidth",
                 0.9995,
@@ -125,7 +415,59 @@ impl Default for PlatePluginParams {
                     max: 0.9999,
                 },
             ),
+            drive: FloatParam::new(
+                "Drive",
+                0.0,
+                FloatRange::Skewed {
+                    min: 0.0,
+                    max: MAX_DRIVE,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            ),
+            character: FloatParam::new("Character", 0.0, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            mode: EnumParam::new("Mode", Mode::Insert),
             wet: FloatParam::new("Wet", 0.500, FloatRange::Linear { min: 0.0, max: 1.0 }),
+            send_level: FloatParam::new(
+                "Send level",
+                util::db_to_gain(0.0),
+                FloatRange::Skewed {
+                    min: util::db_to_gain(-60.0),
+                    max: util::db_to_gain(6.0),
+                    factor: FloatRange::gain_skew_factor(-60.0, 6.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_gain_to_db(2))
+            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+            wet_solo: BoolParam::new("Wet solo", false).non_automatable(),
+            output_gain: FloatParam::new(
+                "Output gain",
+                util::db_to_gain(0.0),
+                FloatRange::Skewed {
+                    min: util::db_to_gain(-24.0),
+                    max: util::db_to_gain(12.0),
+                    factor: FloatRange::gain_skew_factor(-24.0, 12.0),
+                },
+            )
+            .with_smoother(SmoothingStyle::Logarithmic(50.0))
+            .with_unit(" dB")
+            .with_value_to_string(formatters::v2s_f32_gain_to_db(2))
+            .with_string_to_value(formatters::s2v_f32_gain_to_db()),
+            safety: EnumParam::new("Safety", Safety::Off),
+            meter_decay: FloatParam::new(
+                "Meter decay",
+                METER_DECAY,
+                FloatRange::Skewed {
+                    min: 0.1,
+                    max: 10.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" s")
+            .non_automatable(),
+            precision: EnumParam::new("Precision", Precision::Single).non_automatable(),
+            oversampling: EnumParam::new("Oversampling", Oversampling::Off).non_automatable(),
             decay_mod: IntParam::new(
                 "Decay mod",
                 0,
@@ -134,6 +476,77 @@ impl Default for PlatePluginParams {
                     max: (EXCURSION as i32 - 1),
                 },
             ),
+            shimmer: FloatParam::new("Shimmer", 0.0, FloatRange::Linear { min: 0.0, max: 0.5 }),
+            shimmer_interval: IntParam::new(
+                "Shimmer interval",
+                12,
+                IntRange::Linear { min: -24, max: 24 },
+            )
+            .with_unit(" st"),
+            reverse: BoolParam::new("Reverse", false),
+            reverse_length: FloatParam::new(
+                "Reverse length",
+                500.0,
+                FloatRange::Linear {
+                    min: 50.0,
+                    max: 1000.0,
+                },
+            )
+            .with_unit(" ms"),
+            reverse_sync: BoolParam::new("Reverse sync", false),
+            reverse_beats: FloatParam::new(
+                "Reverse beats",
+                1.0,
+                FloatRange::Linear {
+                    min: 0.25,
+                    max: 4.0,
+                },
+            )
+            .with_step_size(0.25),
+            ir_path: Mutex::new(None),
+            ir_start: FloatParam::new(
+                "IR start",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 1000.0,
+                },
+            )
+            .with_unit(" ms")
+            .with_callback(Arc::new({
+                let reload = reload.clone();
+                move |_| reload()
+            })),
+            ir_length: FloatParam::new(
+                "IR length",
+                10000.0,
+                FloatRange::Skewed {
+                    min: 10.0,
+                    max: 10000.0,
+                    factor: FloatRange::skew_factor(-1.0),
+                },
+            )
+            .with_unit(" ms")
+            .with_callback(Arc::new({
+                let reload = reload.clone();
+                move |_| reload()
+            })),
+            ir_stretch: FloatParam::new(
+                "IR stretch",
+                1.0,
+                FloatRange::Linear { min: 0.5, max: 2.0 },
+            )
+            .with_callback(Arc::new({
+                let reload = reload.clone();
+                move |_| reload()
+            })),
+            ir_reverse: BoolParam::new("IR reverse", false).with_callback(Arc::new({
+                let reload = reload.clone();
+                move |_| reload()
+            })),
+            ir_partitioning: EnumParam::new("IR partitioning", Partitioning::NonUniform)
+                .with_callback(Arc::new(move |_| reload())),
+            ir_changed,
         }
     }
 }
@@ -149,9 +562,141 @@ impl From<&PlatePluginParams> for PlateParams<f32> {
             decay_diffusion_2: value.decay_diffusion_2.smoothed.next(),
             damping: value.damping.smoothed.next(),
             decay: value.decay.smoothed.next(),
+            drive: value.drive.smoothed.next(),
+            character: value.character.smoothed.next(),
             decay_modulation: value.decay_mod.smoothed.next() as isize,
+            shimmer: value.shimmer.smoothed.next(),
+            shimmer_interval: value.shimmer_interval.value() as isize,
+            reverse: value.reverse.value(),
+            reverse_window: REVERSE,
+        }
+    }
+}
+
+/// Converts parameters for a plate at the host's rate to one running `factor` times as fast,
+/// so that both sound the same.
+fn oversample(params: PlateParams<f32>, factor: usize) -> PlateParams<f32> {
+    if factor == 1 {
+        return params;
+    }
+    let params = params.scale_filters(factor as f32);
+    PlateParams {
+        predelay: factor * params.predelay,
+        reverse_window: (factor * params.reverse_window).min(REVERSE),
+        ..params
+    }
+}
+
+/// Keeps the wet signal under [`CEILING`] as selected by the safety parameter.
+fn output_stage(limiter: &mut Limiter<f32>, wet_out: [f32; 2], safety: Safety) -> [f32; 2] {
+    match safety {
+        Safety::Off => wet_out,
+        Safety::SoftClip => wet_out.map(|y| soft_clip(y, SOFT_CLIP_KNEE, CEILING)),
+        Safety::Limiter => {
+            let peak = wet_out[0].abs().max(wet_out[1].abs());
+            let gain = limiter.tick(peak);
+            wet_out.map(|y| gain * y)
+        }
+    }
+}
+
+/// Levels of the dry and wet signals while the plugin is active.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Mix {
+    dry: f32,
+    wet: f32,
+}
+
+impl Mix {
+    fn new(mode: Mode, wet: f32, send_level: f32, wet_solo: bool) -> Self {
+        match mode {
+            // The dry signal already reaches the mix through the channel the send comes from.
+            Mode::Send => Self {
+                dry: 0.0,
+                wet: send_level,
+            },
+            Mode::Insert if wet_solo => Self { dry: 0.0, wet: 1.0 },
+            Mode::Insert => Self {
+                dry: 1.0 - wet,
+                wet,
+            },
+        }
+    }
+}
+
+/// Gains of the signal paths at a point of the bypass crossfade.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BypassGains {
+    /// Gain of the signal fed to the reverb.
+    input: f32,
+    dry: f32,
+    wet: f32,
+}
+
+impl BypassGains {
+    /// `fade` runs from 0 when active to 1 when bypassed,
+    /// which passes the input through unchanged in every mode.
+    /// With `spillover` the reverb's input is faded out instead of its output,
+    /// so the tail rings out after bypassing.
+    fn new(fade: f32, mix: Mix, spillover: bool) -> Self {
+        let dry = (1.0 - fade) * mix.dry + fade;
+        if spillover {
+            Self {
+                input: 1.0 - fade,
+                dry,
+                wet: mix.wet,
+            }
+        } else {
+            Self {
+                input: 1.0,
+                dry,
+                wet: (1.0 - fade) * mix.wet,
+            }
+        }
+    }
+}
+
+/// Replaces NaN and infinity from the host with silence.
+fn sanitize(x: f32) -> f32 {
+    if x.is_finite() {
+        x
+    } else {
+        0.0
+    }
+}
+
+/// Delays stereo frames by up to a fixed number of samples.
+struct DryDelay {
+    frames: Vec<[f32; 2]>,
+    head: usize,
+}
+
+impl DryDelay {
+    fn new(max_delay: usize) -> Self {
+        Self {
+            frames: vec![[0.0; 2]; max_delay + 1],
+            head: 0,
         }
     }
+
+    /// Writes a frame and returns the one written `delay` frames before it.
+    fn tick(&mut self, x: [f32; 2], delay: usize) -> [f32; 2] {
+        let len = self.frames.len();
+        self.frames[self.head] = x;
+        let y = self.frames[(self.head + len - delay) % len];
+        self.head = (self.head + 1) % len;
+        y
+    }
+
+    fn clear(&mut self) {
+        self.frames.fill([0.0; 2]);
+        self.head = 0;
+    }
+}
+
+/// Per sample coefficient of a one-pole release with a time constant of `time` seconds.
+fn release(time: f32, sample_rate: f32) -> f32 {
+    (-1.0 / (time * sample_rate)).exp()
 }
 
 impl Plugin for PlatePlugin {
@@ -184,19 +729,75 @@ impl Plugin for PlatePlugin {
     const SAMPLE_ACCURATE_AUTOMATION: bool = true;
 
     type SysExMessage = ();
-    type BackgroundTask = ();
+    type BackgroundTask = Task;
 
     fn params(&self) -> Arc<dyn Params> {
         self.params.clone()
     }
 
+    fn task_executor(&mut self) -> TaskExecutor<Self> {
+        let params = self.params.clone();
+        let loader = self.loader.clone();
+        Box::new(move |task| match task {
+            Task::LoadImpulseResponse => loader.load(&params),
+        })
+    }
+
+    fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
+        editor::create(
+            self.params.clone(),
+            self.meters.clone(),
+            self.analysis.clone(),
+        )
+    }
+
+    fn initialize(
+        &mut self,
+        _audio_io_layout: &AudioIOLayout,
+        buffer_config: &BufferConfig,
+        context: &mut impl InitContext<Self>,
+    ) -> bool {
+        self.sample_rate = buffer_config.sample_rate;
+
+        self.loader
+            .sample_rate
+            .store(buffer_config.sample_rate, Ordering::Relaxed);
+        self.meters
+            .sample_rate
+            .store(buffer_config.sample_rate, Ordering::Relaxed);
+        self.input_meter.reset();
+        self.output_meter.reset();
+        self.limiter
+            .set_params(CEILING, release(LIMITER_RELEASE, buffer_config.sample_rate));
+        self.limiter.clear();
+        self.dry_delay.clear();
+        self.bypass_fade = if self.params.bypass.value() { 1.0 } else { 0.0 };
+        self.params.ir_changed.store(false, Ordering::Relaxed);
+        context.execute(Task::LoadImpulseResponse);
+        true
+    }
+
     fn process(
         &mut self,
         buffer: &mut Buffer,
         _aux: &mut AuxiliaryBuffers,
-        _context: &mut impl ProcessContext<Self>,
+        context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
-        self.process_buffer(buffer)
+        self.tempo = context.transport().tempo;
+        if self.params.ir_changed.swap(false, Ordering::Relaxed) {
+            context.execute_background(Task::LoadImpulseResponse);
+        }
+
+        let reported = self.latency;
+        let status = self.process_buffer(buffer);
+        if self.latency != reported {
+            context.set_latency_samples(self.latency);
+        }
+        status
+    }
+
+    fn filter_state(state: &mut PluginState) {
+        stamp_state_version(state);
     }
 
     fn deactivate(&mut self) {}
@@ -204,20 +805,150 @@ impl Plugin for PlatePlugin {
 
 impl PlatePlugin {
     fn process_buffer(&mut self, buffer: &mut Buffer) -> ProcessStatus {
+        let _ftz = FlushToZero::enable();
         let params: &PlatePluginParams = self.params.deref();
         let wet = params.wet.smoothed.next();
-        self.plate.set_params(params.into());
+        let mix = Mix::new(
+            params.mode.value(),
+            wet,
+            params.send_level.smoothed.next(),
+            params.wet_solo.value(),
+        );
+        let mut plate_params: PlateParams<f32> = params.into();
+        plate_params.reverse_window = self.reverse_window();
+        let factor = params.oversampling.value().factor();
+        if factor != self.upsamplers[0].factor() {
+            self.set_oversampling(factor);
+        }
+        let plate_params = oversample(plate_params, factor);
+        let precision = params.precision.value();
+        if precision != self.precision {
+            self.precision = precision;
+            match precision {
+                Precision::Single => self.plate.clear(),
+                Precision::Double => self.plate_f64.clear(),
+            }
+        }
+        match precision {
+            Precision::Single => self.plate.set_params(plate_params),
+            Precision::Double => self.plate_f64.set_params(plate_params.map(f64::from)),
+        }
+        self.loader.receive(&mut self.convolver);
+        let engine = params.engine.value();
+        self.latency = self.engine_latency(engine) as u32;
+        let latency = self.latency as usize;
+        let safety = params.safety.value();
+        let bypass_target = if params.bypass.value() { 1.0 } else { 0.0 };
+        let bypass_step = 1.0 / (BYPASS_RAMP * self.sample_rate);
+        let spillover = params.spillover.value();
+        let editor_open = params.editor_state.is_open();
+        let meter_decay = params.meter_decay.value();
+        self.input_meter.set_decay(meter_decay, self.sample_rate);
+        self.output_meter.set_decay(meter_decay, self.sample_rate);
         for mut samples in buffer.iter_samples() {
             // The following safe code will crash DAW.
             // let inputs: Vec<f32> = samples.iter_mut().map(|x| *x).collect();
             let inputs = unsafe { [*samples.get_unchecked_mut(0), *samples.get_unchecked_mut(1)] };
-            let plate_out = self.plate.process_2ch(inputs.as_ref());
-            for (out, y) in samples.iter_mut().zip(plate_out) {
-                *out = (1.0 - wet) * *out + wet * y;
+            let inputs = inputs.map(sanitize);
+            self.input_meter.tick(&inputs);
+            self.bypass_fade += (bypass_target - self.bypass_fade).clamp(-bypass_step, bypass_step);
+            let gains = BypassGains::new(self.bypass_fade, mix, spillover);
+            let reverb_in = inputs.map(|x| gains.input * x);
+            let wet_out = match (engine, &mut self.convolver) {
+                (Engine::Convolution, Some(convolver)) => convolver.tick(reverb_in),
+                _ => self.process_plate(reverb_in, precision),
+            };
+            let gain = params.output_gain.smoothed.next();
+            let wet_out = output_stage(&mut self.limiter, wet_out.map(|y| gain * y), safety);
+            self.output_meter.tick(&wet_out);
+            if editor_open {
+                self.analysis.push(0.5 * (wet_out[0] + wet_out[1]));
+            }
+            let dry = self.dry_delay.tick(inputs, latency);
+            for ((out, x), y) in samples.iter_mut().zip(dry).zip(wet_out) {
+                *out = gains.dry * x + gains.wet * y;
             }
         }
+        if editor_open {
+            self.meters.input.publish(&mut self.input_meter);
+            self.meters.output.publish(&mut self.output_meter);
+        }
         ProcessStatus::Normal
     }
+
+    /// Runs the plates at `factor` times the host's rate, clearing their tails.
+    fn set_oversampling(&mut self, factor: usize) {
+        let lengths = PlateLengths::new(factor as f64 * SAMPLE_RATE);
+        self.plate.set_lengths(lengths.clone());
+        self.plate_f64.set_lengths(lengths);
+        for upsampler in &mut self.upsamplers {
+            upsampler.set_factor(factor);
+        }
+        for downsampler in &mut self.downsamplers {
+            downsampler.set_factor(factor);
+        }
+    }
+
+    /// Runs a frame through the plate at the oversampled rate.
+    fn process_plate(&mut self, x: [f32; 2], precision: Precision) -> [f32; 2] {
+        let factor = self.upsamplers[0].factor();
+        let mut inputs = [[0.0; MAX_OVERSAMPLING]; 2];
+        for ((upsampler, x), input) in self.upsamplers.iter_mut().zip(x).zip(&mut inputs) {
+            upsampler.tick(x, &mut input[..factor]);
+        }
+
+        let mut outputs = [[0.0; MAX_OVERSAMPLING]; 2];
+        for (i, (left, right)) in inputs[0][..factor]
+            .iter()
+            .zip(&inputs[1][..factor])
+            .enumerate()
+        {
+            let x = [*left, *right];
+            let y = match precision {
+                Precision::Single => self.plate.process_2ch_checked(&x),
+                Precision::Double => self
+                    .plate_f64
+                    .process_2ch_checked(&x.map(f64::from))
+                    .map(|y| y as f32),
+            };
+            outputs[0][i] = y[0];
+            outputs[1][i] = y[1];
+        }
+
+        let mut y = [0.0; 2];
+        for ((y, downsampler), output) in y.iter_mut().zip(&mut self.downsamplers).zip(&outputs) {
+            *y = downsampler.tick(&output[..factor]);
+        }
+        y
+    }
+
+    /// Latency of `engine` in samples at the host's rate: none for a loaded convolver,
+    /// else that of the plate running at the current precision and of the resamplers.
+    fn engine_latency(&self, engine: Engine) -> usize {
+        if engine == Engine::Convolution && self.convolver.is_some() {
+            return 0;
+        }
+        let plate = match self.precision {
+            Precision::Single => self.plate.latency(),
+            Precision::Double => self.plate_f64.latency(),
+        };
+        let resamplers = self.upsamplers[0].latency() + self.downsamplers[0].latency();
+        (plate + resamplers) / self.upsamplers[0].factor()
+    }
+
+    /// Reverse window in samples, synced to the host tempo when available
+    /// and snapped to [`REVERSE_WINDOW_STEP`].
+    fn reverse_window(&self) -> usize {
+        let params = &self.params;
+        let seconds = match self.tempo {
+            Some(tempo) if params.reverse_sync.value() => {
+                params.reverse_beats.value() as f64 * 60.0 / tempo
+            }
+            _ => params.reverse_length.value() as f64 / 1000.0,
+        };
+        let steps = (seconds * self.sample_rate as f64 / REVERSE_WINDOW_STEP as f64).round();
+        (steps as usize * REVERSE_WINDOW_STEP).clamp(REVERSE_WINDOW_STEP, REVERSE)
+    }
 }
 
 impl ClapPlugin for PlatePlugin {
@@ -246,7 +977,7 @@ nih_export_vst3!(PlatePlugin);
 mod tests {
     use nih_plug::buffer::Buffer;
 
-    use crate::PlatePlugin;
+    use crate::*;
 
     #[test]
     fn basic() {
@@ -264,4 +995,198 @@ mod tests {
 
         plugin.process_buffer(&mut buffer);
     }
+
+    #[test]
+    fn safety() {
+        let mut limiter = Limiter::new(CEILING, release(LIMITER_RELEASE, 44100.0));
+        for safety in [Safety::SoftClip, Safety::Limiter] {
+            for x in [0.1, 20.0, -20.0, 0.1] {
+                let y = output_stage(&mut limiter, [x, 0.5 * x], safety);
+                assert!(y.iter().all(|y| y.abs() <= CEILING));
+            }
+        }
+        assert_eq!(
+            [20.0, 1.0],
+            output_stage(&mut limiter, [20.0, 1.0], Safety::Off)
+        );
+        assert_eq!(
+            [0.1, -0.1],
+            output_stage(&mut limiter, [0.1, -0.1], Safety::SoftClip)
+        );
+    }
+
+    #[test]
+    fn non_finite_input() {
+        let mut plugin = PlatePlugin::default();
+
+        let mut real_buffers = vec![vec![0.5; 44100]; 2];
+        real_buffers[0][10] = f32::NAN;
+        real_buffers[1][20] = f32::INFINITY;
+        {
+            let mut buffer = Buffer::default();
+            unsafe {
+                buffer.set_slices(44100, |output_slices| {
+                    let (first_channel, other_channels) = real_buffers.split_at_mut(1);
+                    *output_slices = vec![&mut first_channel[0], &mut other_channels[0]];
+                })
+            };
+            plugin.process_buffer(&mut buffer);
+        }
+        assert!(real_buffers.iter().flatten().all(|y| y.is_finite()));
+    }
+
+    #[test]
+    fn double_precision() {
+        let mut plugin = PlatePlugin::default();
+        let params = PlateParams {
+            decay: 0.99,
+            ..PlateParams::default()
+        };
+        plugin.plate.set_params(params.clone());
+        plugin.plate_f64.set_params(params.clone().map(f64::from));
+        let mut reference = Plate::<f64>::new(SAMPLE_RATE, 0);
+        reference.set_params(params.map(f64::from));
+
+        // Squared error against a plate running and read out in f64.
+        let mut noise = [0.0; 2];
+        for t in 0..10 * SAMPLE_RATE as usize {
+            let x = if t == 0 { 1.0 } else { 0.0 };
+            let y = reference.process_2ch(&[x as f64]);
+            for (precision, noise) in [Precision::Single, Precision::Double]
+                .into_iter()
+                .zip(&mut noise)
+            {
+                let y_hat = plugin.process_plate([x, x], precision);
+                *noise += (y_hat[0] as f64 - y[0]).powi(2) + (y_hat[1] as f64 - y[1]).powi(2);
+            }
+        }
+        // The f32 tank's rounding noise builds up over the long tail,
+        // while the f64 one only rounds at the edges.
+        assert!(100.0 * noise[1] < noise[0]);
+    }
+
+    #[test]
+    fn oversampling() {
+        let params = PlateParams {
+            predelay: 10,
+            bandwidth: 0.6,
+            damping: 0.3,
+            reverse_window: 1000,
+            ..PlateParams::default()
+        };
+        assert_eq!(params, oversample(params.clone(), 1));
+        let oversampled = oversample(params.clone(), 2);
+        assert_eq!(
+            (20, 2000),
+            (oversampled.predelay, oversampled.reverse_window)
+        );
+        // Two steps of the oversampled filters make one at the host's rate.
+        assert!(((1.0 - oversampled.bandwidth).powi(2) - 0.4).abs() < 1e-6);
+        assert!((oversampled.damping.powi(2) - 0.3).abs() < 1e-6);
+        assert_eq!(params.decay, oversampled.decay);
+
+        let mut plugin = PlatePlugin::default();
+        assert_eq!(0, plugin.engine_latency(Engine::Plate));
+        for factor in [2, 4] {
+            plugin.set_oversampling(factor);
+            plugin.plate.set_params(oversample(params.clone(), factor));
+            assert_eq!(PHASE_TAPS - 1, plugin.engine_latency(Engine::Plate));
+            for t in 0..10000 {
+                let x = if t == 0 { 1.0 } else { 0.0 };
+                let y = plugin.process_plate([x, x], Precision::Single);
+                assert!(y.iter().all(|y| y.is_finite() && y.abs() < 1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn convolution_latency() {
+        let mut plugin = PlatePlugin::default();
+        plugin.set_oversampling(2);
+        // The plate stands in until an impulse response is loaded.
+        assert_eq!(PHASE_TAPS - 1, plugin.engine_latency(Engine::Convolution));
+
+        let impulse_response = ImpulseResponse {
+            channels: vec![vec![0.5, 0.25]],
+            sample_rate: 44100.0,
+        };
+        plugin.convolver = Some(StereoConvolver::new(
+            &impulse_response,
+            Partitioning::Uniform,
+        ));
+        assert_eq!(0, plugin.engine_latency(Engine::Convolution));
+        assert_eq!(PHASE_TAPS - 1, plugin.engine_latency(Engine::Plate));
+    }
+
+    #[test]
+    fn bypass() {
+        let mix = Mix::new(Mode::Insert, 0.3, 1.0, false);
+        let active = BypassGains::new(0.0, mix, true);
+        assert_eq!(BypassGains::new(0.0, mix, false), active);
+        assert_eq!((1.0, 0.7, 0.3), (active.input, active.dry, active.wet));
+
+        let bypassed = BypassGains::new(1.0, mix, false);
+        assert_eq!((1.0, 0.0), (bypassed.dry, bypassed.wet));
+
+        let spillover = BypassGains::new(1.0, mix, true);
+        assert_eq!(
+            (0.0, 1.0, 0.3),
+            (spillover.input, spillover.dry, spillover.wet)
+        );
+
+        let half = BypassGains::new(0.5, mix, false);
+        assert!((half.dry - 0.85).abs() < 1e-6);
+        assert!((half.wet - 0.15).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bypass_send() {
+        // A bypassed send passes its input on like any other bypassed plugin.
+        let mix = Mix::new(Mode::Send, 0.3, 0.5, false);
+        let bypassed = BypassGains::new(1.0, mix, false);
+        assert_eq!((1.0, 0.0), (bypassed.dry, bypassed.wet));
+
+        let spillover = BypassGains::new(1.0, mix, true);
+        assert_eq!(
+            (0.0, 1.0, 0.5),
+            (spillover.input, spillover.dry, spillover.wet)
+        );
+
+        let half = BypassGains::new(0.5, mix, false);
+        assert!((half.dry - 0.5).abs() < 1e-6);
+        assert!((half.wet - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn mix() {
+        let send = Mix::new(Mode::Send, 0.3, 0.5, true);
+        assert_eq!((0.0, 0.5), (send.dry, send.wet));
+        assert_eq!(1.0, BypassGains::new(1.0, send, false).dry);
+
+        let solo = Mix::new(Mode::Insert, 0.3, 0.5, true);
+        assert_eq!((0.0, 1.0), (solo.dry, solo.wet));
+        assert_eq!(1.0, BypassGains::new(1.0, solo, false).dry);
+    }
+
+    #[test]
+    fn dry_delay() {
+        let mut delay = DryDelay::new(4);
+        assert_eq!([1.0, -1.0], delay.tick([1.0, -1.0], 0));
+        assert_eq!([0.0; 2], delay.tick([2.0, -2.0], 4));
+        assert_eq!([1.0, -1.0], delay.tick([3.0, -3.0], 2));
+        assert_eq!([1.0, -1.0], delay.tick([4.0, -4.0], 3));
+        assert_eq!([4.0, -4.0], delay.tick([5.0, -5.0], 1));
+        delay.clear();
+        assert_eq!([0.0; 2], delay.tick([6.0, -6.0], 1));
+    }
+
+    #[test]
+    fn reverse_window() {
+        let mut plugin = PlatePlugin::default();
+        let window = plugin.reverse_window();
+        assert_eq!(0, window % REVERSE_WINDOW_STEP);
+        // The latency holds still while the rate or tempo only drifts.
+        plugin.sample_rate += 0.5;
+        assert_eq!(window, plugin.reverse_window());
+    }
 }
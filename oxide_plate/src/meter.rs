@@ -0,0 +1,169 @@
+//! Level metering shared between the audio thread and the editor.
+
+use nih_plug::prelude::{util, AtomicF32};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Averaging time of the RMS level in seconds.
+pub const RMS_WINDOW: f32 = 0.3;
+
+/// Level from which a sample counts as clipped.
+pub const CLIP_LEVEL: f32 = 1.0;
+
+/// Peak and RMS ballistics of a signal, run on the audio thread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ballistics {
+    peak: f32,
+    mean_square: f32,
+    release: f32,
+    average: f32,
+    clipped: bool,
+}
+
+impl Ballistics {
+    /// Creates a new instance whose peak falls by 60 dB in `decay` seconds.
+    pub fn new(decay: f32, sample_rate: f32) -> Self {
+        let mut ballistics = Self {
+            peak: 0.0,
+            mean_square: 0.0,
+            release: 0.0,
+            average: 0.0,
+            clipped: false,
+        };
+        ballistics.set_decay(decay, sample_rate);
+        ballistics
+    }
+
+    /// Sets the time in seconds for the peak to fall by 60 dB.
+    pub fn set_decay(&mut self, decay: f32, sample_rate: f32) {
+        self.release = util::db_to_gain(-60.0 / (decay * sample_rate));
+        self.average = (-1.0 / (RMS_WINDOW * sample_rate)).exp();
+    }
+
+    /// Feeds one frame of samples.
+    pub fn tick(&mut self, xs: &[f32]) {
+        let peak = xs.iter().fold(0.0f32, |peak, x| peak.max(x.abs()));
+        let square = xs.iter().map(|x| x * x).sum::<f32>() / xs.len().max(1) as f32;
+
+        self.peak = peak.max(self.peak * self.release);
+        self.mean_square = self.average * self.mean_square + (1.0 - self.average) * square;
+        self.clipped |= peak >= CLIP_LEVEL;
+    }
+
+    /// Peak level in dBFS.
+    pub fn peak(&self) -> f32 {
+        util::gain_to_db(self.peak)
+    }
+
+    /// RMS level in dBFS.
+    pub fn rms(&self) -> f32 {
+        util::gain_to_db(self.mean_square.sqrt())
+    }
+
+    /// Returns whether a sample clipped since the last call.
+    pub fn take_clip(&mut self) -> bool {
+        std::mem::take(&mut self.clipped)
+    }
+
+    pub fn reset(&mut self) {
+        self.peak = 0.0;
+        self.mean_square = 0.0;
+        self.clipped = false;
+    }
+}
+
+/// Levels of one meter, published lock-free to the editor.
+#[derive(Debug)]
+pub struct Level {
+    /// Peak level in dBFS.
+    pub peak: AtomicF32,
+    /// RMS level in dBFS.
+    pub rms: AtomicF32,
+    /// Latched when a sample clipped, until the editor resets it.
+    pub clip: AtomicBool,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self {
+            peak: AtomicF32::new(util::MINUS_INFINITY_DB),
+            rms: AtomicF32::new(util::MINUS_INFINITY_DB),
+            clip: AtomicBool::new(false),
+        }
+    }
+}
+
+impl Level {
+    pub fn publish(&self, ballistics: &mut Ballistics) {
+        self.peak.store(ballistics.peak(), Ordering::Relaxed);
+        self.rms.store(ballistics.rms(), Ordering::Relaxed);
+        if ballistics.take_clip() {
+            self.clip.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Levels published by the audio thread for the editor.
+#[derive(Debug)]
+pub struct Meters {
+    /// Dry input.
+    pub input: Level,
+    /// Wet output of the reverb, before the dry/wet mix.
+    pub output: Level,
+    /// Sample rate of the tank, for the time axis of the decay curve.
+    pub sample_rate: AtomicF32,
+}
+
+impl Default for Meters {
+    fn default() -> Self {
+        Self {
+            input: Level::default(),
+            output: Level::default(),
+            sample_rate: AtomicF32::new(44100.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peak_decay() {
+        let mut ballistics = Ballistics::new(1.0, 1000.0);
+        ballistics.tick(&[0.5, -1.0]);
+        assert_eq!(0.0, ballistics.peak());
+        for _ in 0..500 {
+            ballistics.tick(&[0.0, 0.0]);
+        }
+        assert!((ballistics.peak() + 30.0).abs() < 0.1);
+        ballistics.tick(&[0.25, 0.0]);
+        assert!((ballistics.peak() - util::gain_to_db(0.25)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn rms() {
+        let mut ballistics = Ballistics::new(1.0, 48000.0);
+        for t in 0..48000 * 3 {
+            let x = (t as f32 * 0.1).sin();
+            ballistics.tick(&[x, x]);
+        }
+        assert!((ballistics.rms() + 3.01).abs() < 0.1);
+        assert!(ballistics.peak().abs() < 0.1);
+    }
+
+    #[test]
+    fn clip() {
+        let mut ballistics = Ballistics::new(1.0, 48000.0);
+        let level = Level::default();
+        ballistics.tick(&[0.9]);
+        level.publish(&mut ballistics);
+        assert!(!level.clip.load(Ordering::Relaxed));
+
+        ballistics.tick(&[-1.2]);
+        ballistics.tick(&[0.0]);
+        level.publish(&mut ballistics);
+        level.publish(&mut ballistics);
+        assert!(level.clip.load(Ordering::Relaxed));
+        assert!(!ballistics.take_clip());
+    }
+}
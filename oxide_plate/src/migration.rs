@@ -0,0 +1,62 @@
+//! Versioning of saved states.
+//!
+//! States carry the version of the parameter layout they were saved with.
+//! No stored value has changed meaning since the version field was added,
+//! so older states load as they are; the first change that does has to convert them here.
+
+use nih_plug::wrapper::state::PluginState;
+
+/// Version of the parameter layout written by this build.
+///
+/// - 0: States saved before the version field was added.
+/// - 1: The same layout, carrying the version field.
+pub const STATE_VERSION: u32 = 1;
+
+/// Id of the persisted field holding the state version.
+pub const STATE_VERSION_FIELD: &str = "state_version";
+
+/// Marks a loaded state with the current version, which is saved along with it.
+pub fn stamp_state_version(state: &mut PluginState) {
+    state.fields.insert(
+        STATE_VERSION_FIELD.to_owned(),
+        serde_json::to_string(&STATE_VERSION).unwrap(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlatePlugin;
+    use nih_plug::prelude::Plugin;
+
+    fn load(json: &str) -> PluginState {
+        let mut state: PluginState = serde_json::from_str(json).unwrap();
+        PlatePlugin::filter_state(&mut state);
+        state
+    }
+
+    fn params(json: &str) -> serde_json::Value {
+        let state: PluginState = serde_json::from_str(json).unwrap();
+        serde_json::to_value(&state.params).unwrap()
+    }
+
+    fn version(state: &PluginState) -> Option<&str> {
+        state.fields.get(STATE_VERSION_FIELD).map(String::as_str)
+    }
+
+    #[test]
+    fn version_0() {
+        let json = include_str!("../fixtures/state_v0.json");
+        let state = load(json);
+        assert_eq!(params(json), serde_json::to_value(&state.params).unwrap());
+        assert_eq!(Some("1"), version(&state));
+    }
+
+    #[test]
+    fn version_1() {
+        let json = include_str!("../fixtures/state_v1.json");
+        let state = load(json);
+        assert_eq!(params(json), serde_json::to_value(&state.params).unwrap());
+        assert_eq!(Some("1"), version(&state));
+    }
+}
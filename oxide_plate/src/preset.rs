@@ -0,0 +1,136 @@
+//! Capturing and applying presets of the plugin's parameters.
+//!
+//! The pinned nih-plug revision exposes neither CLAP preset discovery nor VST3 program lists,
+//! so presets are applied through the editor's [`GuiContext`],
+//! which records every change with the host like a user edit.
+
+use nih_plug::prelude::{GuiContext, ParamPtr, Params};
+pub use oxide_plate_preset::*;
+
+/// Parameters that make up the sound, which presets store and set.
+///
+/// The routing, safety and host bypass stay as they are when a preset is applied.
+pub const TONE_PARAMS: &[&str] = &[
+    "predelay",
+    "bandwidth",
+    "input_diffusion_1",
+    "input_diffusion_2",
+    "decay_diffusion_1",
+    "decay_diffusion_2",
+    "damping",
+    "decay",
+    "drive",
+    "character",
+    "decay_mod",
+    "shimmer",
+    "shimmer_interval",
+    "reverse",
+    "reverse_length",
+    "reverse_sync",
+    "reverse_beats",
+    "wet",
+];
+
+/// Captures the current values of the tone parameters.
+pub fn capture(name: &str, author: &str, tags: &[&str], params: &impl Params) -> Preset {
+    let params = params
+        .param_map()
+        .into_iter()
+        .filter(|(id, _, _)| TONE_PARAMS.contains(&id.as_str()))
+        .map(|(id, ptr, _)| (id, unsafe { ptr.unmodulated_plain_value() }))
+        .collect();
+    Preset {
+        version: PRESET_VERSION,
+        name: name.to_owned(),
+        author: author.to_owned(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        params,
+    }
+}
+
+/// Normalized values of the tone parameters set by `preset`,
+/// with the ones it leaves out at their defaults.
+fn changes(preset: &Preset, params: &impl Params) -> Vec<(String, ParamPtr, f32)> {
+    params
+        .param_map()
+        .into_iter()
+        .filter(|(id, _, _)| TONE_PARAMS.contains(&id.as_str()))
+        .map(|(id, ptr, _)| {
+            let normalized = match preset.params.get(&id) {
+                Some(value) => unsafe { ptr.preview_normalized(*value) },
+                None => unsafe { ptr.default_normalized_value() },
+            };
+            (id, ptr, normalized)
+        })
+        .collect()
+}
+
+/// Sets the tone parameters through the editor so that the host records the change.
+pub fn apply(preset: &Preset, params: &impl Params, context: &dyn GuiContext) {
+    for (_, ptr, normalized) in changes(preset, params) {
+        unsafe {
+            context.raw_begin_set_parameter(ptr);
+            context.raw_set_parameter_normalized(ptr, normalized);
+            context.raw_end_set_parameter(ptr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlatePluginParams;
+
+    #[test]
+    fn factory_presets_use_tone_params() {
+        for preset in factory_bank() {
+            for id in preset.params.keys() {
+                assert!(
+                    TONE_PARAMS.contains(&id.as_str()),
+                    "{}: {id} is not a tone parameter",
+                    preset.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tone_params_exist() {
+        let params = PlatePluginParams::default();
+        let ids: Vec<String> = params
+            .param_map()
+            .into_iter()
+            .map(|(id, _, _)| id)
+            .collect();
+        for id in TONE_PARAMS {
+            assert!(
+                ids.iter().any(|known| known == id),
+                "unknown parameter {id}"
+            );
+        }
+    }
+
+    #[test]
+    fn apply_keeps_routing() {
+        let params = PlatePluginParams::default();
+        for preset in factory_bank() {
+            let changes = changes(&preset, &params);
+            assert_eq!(TONE_PARAMS.len(), changes.len());
+            for (id, _, _) in changes {
+                assert!(id != "bypass" && id != "mode", "{}: sets {id}", preset.name);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let params = PlatePluginParams::default();
+        let preset = capture("Default", "", &["plate"], &params);
+        assert_eq!(Some(&50.0), preset.params.get("predelay"));
+        assert_eq!(None, preset.params.get("bypass"));
+        assert_eq!(
+            preset,
+            Preset::from_json(&preset.to_json().unwrap()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,445 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use oxide_plate_dsp::{plate::*, wav::*};
+use oxide_plate_preset::*;
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+/// Renders the oxide plate reverb offline.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Runs the plate over an audio file.
+    Render {
+        /// WAV or FLAC file to process.
+        input: PathBuf,
+        /// WAV file to write.
+        output: PathBuf,
+        /// Writes a mono file by averaging both output channels.
+        #[arg(long)]
+        mono: bool,
+        #[command(flatten)]
+        options: Options,
+    },
+    /// Writes the plate's response to a unit impulse, for convolution reverbs.
+    ImpulseResponse {
+        /// WAV file to write.
+        output: PathBuf,
+        /// Sample rate of the response.
+        #[arg(long, default_value_t = 48000)]
+        sample_rate: u32,
+        #[command(flatten)]
+        options: Options,
+    },
+}
+
+/// Options shared by the subcommands.
+#[derive(clap::Args, Debug)]
+struct Options {
+    /// Preset file, overridden by the flags.
+    #[arg(long)]
+    preset: Option<PathBuf>,
+    /// Factory preset by name, overridden by the flags.
+    #[arg(long, conflicts_with = "preset")]
+    factory: Option<String>,
+    #[command(flatten)]
+    settings: Settings,
+    /// Sample format of the output.
+    #[arg(long, value_enum, default_value_t = Format::Int24)]
+    format: Format,
+    /// Level in dBFS below which the tail counts as decayed.
+    #[arg(long, default_value_t = -90.0, allow_negative_numbers = true)]
+    tail_threshold: f32,
+    /// Longest tail rendered after the input ends, in seconds.
+    #[arg(long, default_value_t = 30.0)]
+    max_tail: f32,
+}
+
+impl Options {
+    /// Settings from the flags, with the rest taken from the preset if any.
+    fn settings(&self, sample_rate: u32) -> Result<Settings, Box<dyn Error>> {
+        let preset = match (&self.preset, &self.factory) {
+            (Some(path), _) => Some(Preset::load(path)?),
+            (_, Some(name)) => Some(
+                factory_bank()
+                    .into_iter()
+                    .find(|preset| preset.name.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| format!("no factory preset named {name}"))?,
+            ),
+            _ => None,
+        };
+        let settings = self.settings.clone();
+        Ok(match &preset {
+            Some(preset) => settings.or(Settings::from_preset(preset, sample_rate)),
+            None => settings,
+        })
+    }
+
+    /// Tail threshold as a gain.
+    fn threshold(&self) -> f32 {
+        10f32.powf(self.tail_threshold / 20.0)
+    }
+
+    /// Longest tail in samples.
+    fn max_tail(&self, sample_rate: u32) -> usize {
+        (self.max_tail * sample_rate as f32) as usize
+    }
+}
+
+/// Plate settings, every one of them optional so that flags can override a preset.
+///
+/// The bandwidth and damping are the filters' coefficients at the plate's native rate
+/// of 29761 Hz, and are scaled to keep their cutoff at the file's rate.
+#[derive(clap::Args, Debug, Default, Clone, PartialEq)]
+struct Settings {
+    /// Pre delay in milliseconds.
+    #[arg(long)]
+    predelay: Option<f32>,
+    #[arg(long)]
+    bandwidth: Option<f32>,
+    #[arg(long)]
+    input_diffusion_1: Option<f32>,
+    #[arg(long)]
+    input_diffusion_2: Option<f32>,
+    #[arg(long)]
+    decay_diffusion_1: Option<f32>,
+    #[arg(long)]
+    decay_diffusion_2: Option<f32>,
+    #[arg(long)]
+    damping: Option<f32>,
+    #[arg(long)]
+    decay: Option<f32>,
+    /// Gain into the tank's saturation, 0 to bypass it.
+    #[arg(long)]
+    drive: Option<f32>,
+    /// Asymmetry of the tank's saturation, from 0 to 1.
+    #[arg(long)]
+    character: Option<f32>,
+    /// Decay modulation in samples.
+    #[arg(long, allow_negative_numbers = true)]
+    decay_mod: Option<isize>,
+    #[arg(long)]
+    shimmer: Option<f32>,
+    /// Shimmer interval in semitones.
+    #[arg(long, allow_negative_numbers = true)]
+    shimmer_interval: Option<isize>,
+    /// Mix between the dry and the wet signal.
+    #[arg(long)]
+    wet: Option<f32>,
+}
+
+impl Settings {
+    /// Reads the plate parameters of a preset, whose pre delay is given in samples.
+    fn from_preset(preset: &Preset, sample_rate: u32) -> Settings {
+        let get = |id: &str| preset.params.get(id).copied();
+        Settings {
+            predelay: get("predelay").map(|samples| samples * 1000.0 / sample_rate as f32),
+            bandwidth: get("bandwidth"),
+            input_diffusion_1: get("input_diffusion_1"),
+            input_diffusion_2: get("input_diffusion_2"),
+            decay_diffusion_1: get("decay_diffusion_1"),
+            decay_diffusion_2: get("decay_diffusion_2"),
+            damping: get("damping"),
+            decay: get("decay"),
+            drive: get("drive"),
+            character: get("character"),
+            decay_mod: get("decay_mod").map(|x| x as isize),
+            shimmer: get("shimmer"),
+            shimmer_interval: get("shimmer_interval").map(|x| x as isize),
+            wet: get("wet"),
+        }
+    }
+
+    /// Takes the values missing in `self` from `other`.
+    fn or(self, other: Settings) -> Settings {
+        Settings {
+            predelay: self.predelay.or(other.predelay),
+            bandwidth: self.bandwidth.or(other.bandwidth),
+            input_diffusion_1: self.input_diffusion_1.or(other.input_diffusion_1),
+            input_diffusion_2: self.input_diffusion_2.or(other.input_diffusion_2),
+            decay_diffusion_1: self.decay_diffusion_1.or(other.decay_diffusion_1),
+            decay_diffusion_2: self.decay_diffusion_2.or(other.decay_diffusion_2),
+            damping: self.damping.or(other.damping),
+            decay: self.decay.or(other.decay),
+            drive: self.drive.or(other.drive),
+            character: self.character.or(other.character),
+            decay_mod: self.decay_mod.or(other.decay_mod),
+            shimmer: self.shimmer.or(other.shimmer),
+            shimmer_interval: self.shimmer_interval.or(other.shimmer_interval),
+            wet: self.wet.or(other.wet),
+        }
+    }
+
+    /// Parameters of a plate at the native [`SAMPLE_RATE`],
+    /// to be [scaled](PlateParams::at_sample_rate) to the file's rate.
+    fn plate_params(&self) -> PlateParams<f32> {
+        let default = PlateParams::default();
+        let predelay = self.predelay.unwrap_or(0.0) as f64 * SAMPLE_RATE / 1000.0;
+        PlateParams {
+            predelay: (predelay.round() as usize).max(1),
+            bandwidth: self.bandwidth.unwrap_or(default.bandwidth),
+            input_diffusion_1: self.input_diffusion_1.unwrap_or(default.input_diffusion_1),
+            input_diffusion_2: self.input_diffusion_2.unwrap_or(default.input_diffusion_2),
+            decay_diffusion_1: self.decay_diffusion_1.unwrap_or(default.decay_diffusion_1),
+            decay_diffusion_2: self.decay_diffusion_2.unwrap_or(default.decay_diffusion_2),
+            damping: self.damping.unwrap_or(default.damping),
+            decay: self.decay.unwrap_or(default.decay),
+            drive: self.drive.unwrap_or(default.drive),
+            character: self.character.unwrap_or(default.character),
+            decay_modulation: self
+                .decay_mod
+                .unwrap_or(default.decay_modulation)
+                .clamp(-(EXCURSION as isize - 1), EXCURSION as isize - 1),
+            shimmer: self.shimmer.unwrap_or(default.shimmer),
+            shimmer_interval: self.shimmer_interval.unwrap_or(default.shimmer_interval),
+            ..default
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Int16,
+    Int24,
+    Float32,
+}
+
+impl From<Format> for SampleFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Int16 => SampleFormat::Int16,
+            Format::Int24 => SampleFormat::Int24,
+            Format::Float32 => SampleFormat::Float32,
+        }
+    }
+}
+
+/// Decoded audio with one buffer per channel.
+/// Reads a mono or stereo WAV or FLAC file.
+fn read(path: &Path) -> Result<Audio, Box<dyn Error>> {
+    let is_flac = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("flac"));
+    let audio = if is_flac {
+        let mut reader = claxon::FlacReader::open(path)?;
+        let info = reader.streaminfo();
+        let scale = 1.0 / (1i64 << (info.bits_per_sample - 1)) as f32;
+        let samples = reader
+            .samples()
+            .map(|x| x.map(|x| x as f32 * scale))
+            .collect::<Result<Vec<_>, _>>()?;
+        let channels = info.channels as usize;
+        Audio {
+            channels: (0..channels)
+                .map(|channel| {
+                    samples
+                        .iter()
+                        .skip(channel)
+                        .step_by(channels)
+                        .copied()
+                        .collect()
+                })
+                .collect(),
+            sample_rate: info.sample_rate,
+        }
+    } else {
+        Audio::read(path)?
+    };
+
+    let channels = audio.channels.len();
+    if !(1..=2).contains(&channels) {
+        return Err(format!("{channels} channels are not supported").into());
+    }
+    Ok(audio)
+}
+
+/// Averages the channels into one.
+fn into_mono(audio: Audio) -> Audio {
+    let count = audio.channels.len() as f32;
+    let mono = (0..audio.len())
+        .map(|n| audio.channels.iter().map(|channel| channel[n]).sum::<f32>() / count)
+        .collect();
+    Audio {
+        channels: vec![mono],
+        sample_rate: audio.sample_rate,
+    }
+}
+
+/// Runs the plate over the input and lets the tail ring out
+/// until it stays below `threshold` or `max_tail` samples have been rendered.
+fn render(input: &Audio, settings: &Settings, threshold: f32, max_tail: usize) -> Audio {
+    let params = settings
+        .plate_params()
+        .at_sample_rate(input.sample_rate as f64);
+    let mut plate = Plate::new(input.sample_rate as f64, params.predelay);
+    plate.set_params(params);
+
+    let wet = settings.wet.unwrap_or(0.5);
+    let last = input.channels.len() - 1;
+    let mut output: Vec<Vec<f32>> = (0..2)
+        .map(|_| {
+            let mut channel = Vec::with_capacity(input.len() + max_tail);
+            channel.resize(input.len(), 0.0);
+            channel
+        })
+        .collect();
+    let inputs: Vec<&[f32]> = input.channels.iter().map(Vec::as_slice).collect();
+    let mut outputs: Vec<&mut [f32]> = output.iter_mut().map(Vec::as_mut_slice).collect();
+    plate.process_block(&inputs, &mut outputs);
+    for (channel, dry) in output.iter_mut().zip([0, last]) {
+        for (y, dry) in channel.iter_mut().zip(&input.channels[dry]) {
+            *y = (1.0 - wet) * dry + wet * *y;
+        }
+    }
+
+    for y in plate.tail(threshold / wet, max_tail) {
+        for (channel, y) in output.iter_mut().zip(y) {
+            channel.push(wet * y);
+        }
+    }
+    // The tail ends on a stretch below the threshold, which isn't worth keeping.
+    while output
+        .iter()
+        .all(|channel| channel.len() > input.len() && channel.last().unwrap().abs() < threshold)
+    {
+        for channel in output.iter_mut() {
+            channel.pop();
+        }
+    }
+
+    Audio {
+        channels: output,
+        sample_rate: input.sample_rate,
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    match Args::parse().command {
+        Command::Render {
+            input,
+            output,
+            mono,
+            options,
+        } => {
+            let input = read(&input)?;
+            let settings = options.settings(input.sample_rate)?;
+            let max_tail = options.max_tail(input.sample_rate);
+            let mut rendered = render(&input, &settings, options.threshold(), max_tail);
+            if mono {
+                rendered = into_mono(rendered);
+            }
+            rendered.write(&output, options.format.into())?;
+        }
+        Command::ImpulseResponse {
+            output,
+            sample_rate,
+            options,
+        } => {
+            let settings = options.settings(sample_rate)?;
+            let impulse_response = Audio::impulse_response(
+                settings.plate_params(),
+                sample_rate,
+                options.threshold(),
+                options.max_tail(sample_rate),
+            );
+            impulse_response.write(&output, options.format.into())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn impulse(sample_rate: u32, length: usize) -> Audio {
+        let mut channel = vec![0.0; length];
+        channel[0] = 1.0;
+        Audio {
+            channels: vec![channel],
+            sample_rate,
+        }
+    }
+
+    #[test]
+    fn tail() {
+        let settings = Settings::default();
+        let output = render(&impulse(48000, 100), &settings, 1e-5, 48000 * 30);
+        assert_eq!(2, output.channels.len());
+        assert!(output.len() > 100);
+        assert!(output.len() < 100 + 48000 * 30);
+        // Only the decayed part of the tail is cut.
+        assert!(output
+            .channels
+            .iter()
+            .any(|channel| channel.last().unwrap().abs() >= 1e-5));
+
+        let dry = Settings {
+            wet: Some(0.0),
+            ..Default::default()
+        };
+        let output = render(&impulse(48000, 100), &dry, 1e-5, 48000 * 30);
+        assert_eq!(impulse(48000, 100).channels[0], output.channels[1]);
+    }
+
+    #[test]
+    fn sample_rate_scaling() {
+        let settings = Settings {
+            wet: Some(1.0),
+            ..Default::default()
+        };
+        let onset = |sample_rate| {
+            let output = render(&impulse(sample_rate, 1000), &settings, 1e-5, 0);
+            output.channels[0].iter().position(|y| *y != 0.0)
+        };
+        // Twice the sample rate, twice the samples before the first reflection.
+        let (single, double) = (onset(24000).unwrap(), onset(48000).unwrap());
+        assert!((2 * single).abs_diff(double) <= 2);
+    }
+
+    #[test]
+    fn filter_scaling() {
+        let settings = Settings {
+            predelay: Some(10.0),
+            bandwidth: Some(0.6),
+            damping: Some(0.3),
+            ..Default::default()
+        };
+        let native = settings.plate_params().at_sample_rate(SAMPLE_RATE);
+        assert_eq!((SAMPLE_RATE / 100.0).round() as usize, native.predelay);
+        assert!((native.bandwidth - 0.6).abs() < 1e-6);
+        assert!((native.damping - 0.3).abs() < 1e-6);
+        // Two steps at twice the rate make one at the native rate.
+        let double = settings.plate_params().at_sample_rate(2.0 * SAMPLE_RATE);
+        assert_eq!(2 * native.predelay, double.predelay);
+        assert!(((1.0 - double.bandwidth).powi(2) - 0.4).abs() < 1e-6);
+        assert!((double.damping.powi(2) - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn flags_override_preset() {
+        let preset = Preset::from_json(
+            r#"{
+                "version": 1,
+                "name": "Test",
+                "params": { "predelay": 480, "decay": 0.8, "wet": 0.3, "engine": 0 }
+            }"#,
+        )
+        .unwrap();
+        let flags = Settings {
+            wet: Some(1.0),
+            ..Default::default()
+        };
+        let settings = flags.or(Settings::from_preset(&preset, 48000));
+        assert_eq!(Some(10.0), settings.predelay);
+        assert_eq!(Some(0.8), settings.decay);
+        assert_eq!(Some(1.0), settings.wet);
+    }
+}
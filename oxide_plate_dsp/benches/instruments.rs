@@ -0,0 +1,163 @@
+//! Per sample cost of each instrument.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use oxide_plate_dsp::instruments::*;
+use std::num::NonZeroUsize;
+
+const BLOCK: usize = 4096;
+
+/// Lengths of the delay lines, from a short diffuser to the longest tank delay at 192 kHz.
+const LENGTHS: [usize; 3] = [142, 4453, 19392];
+
+fn input() -> Vec<f32> {
+    (0..BLOCK as u32)
+        .map(|t| t.wrapping_mul(1664525).wrapping_add(1013904223) as f32 / u32::MAX as f32 - 0.5)
+        .collect()
+}
+
+fn delay(c: &mut Criterion) {
+    let input = input();
+    let mut group = c.benchmark_group("delay");
+    group.throughput(Throughput::Elements(BLOCK as u64));
+    for length in LENGTHS {
+        let mut buffer = vec![0.0f32; length + 1];
+        let mut delay = Delay::new(&mut buffer);
+        let tap = NonZeroUsize::new(length).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(length), &input, |b, input| {
+            b.iter(|| {
+                let mut acc = 0.0;
+                for x in input {
+                    delay.write(*x);
+                    acc += *delay.read(tap);
+                }
+                black_box(acc)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn apf(c: &mut Criterion) {
+    let input = input();
+    let mut group = c.benchmark_group("apf");
+    group.throughput(Throughput::Elements(BLOCK as u64));
+    for length in LENGTHS {
+        let mut buffer = vec![0.0f32; length + 1];
+        let mut apf = APF::with_params(&mut buffer, NonZeroUsize::new(length).unwrap(), 0.7, 0.7);
+        group.bench_with_input(BenchmarkId::from_parameter(length), &input, |b, input| {
+            b.iter(|| {
+                for x in input {
+                    black_box(apf.tick(*x));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn iir<const ORDER: usize>(c: &mut Criterion) {
+    let input = input();
+    let mut group = c.benchmark_group("iir");
+    group.throughput(Throughput::Elements(BLOCK as u64));
+    let mut buffer = [0.0f32; ORDER];
+    let mut iir = IIR::<_, ORDER>::new_with_params(&mut buffer, [0.5 / ORDER as f32; ORDER], 0.5);
+    group.bench_with_input(BenchmarkId::from_parameter(ORDER), &input, |b, input| {
+        b.iter(|| {
+            for x in input {
+                black_box(iir.tick(*x));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn pitch_shifter(c: &mut Criterion) {
+    let input = input();
+    let mut group = c.benchmark_group("pitch_shifter");
+    group.throughput(Throughput::Elements(BLOCK as u64));
+    let mut buffer = vec![0.0f32; 2048];
+    let mut shifter = PitchShifter::new(&mut buffer);
+    shifter.set_ratio(interval_to_ratio(12));
+    group.bench_with_input(BenchmarkId::from_parameter("octave"), &input, |b, input| {
+        b.iter(|| {
+            for x in input {
+                black_box(shifter.tick(*x));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn reverser(c: &mut Criterion) {
+    let input = input();
+    let mut group = c.benchmark_group("reverser");
+    group.throughput(Throughput::Elements(BLOCK as u64));
+    let mut buffer = vec![0.0f32; 2 * 8192];
+    let mut reverser = Reverser::new(&mut buffer);
+    reverser.set_window(NonZeroUsize::new(4096).unwrap());
+    group.bench_with_input(BenchmarkId::from_parameter(4096), &input, |b, input| {
+        b.iter(|| {
+            for x in input {
+                black_box(reverser.tick(*x));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn oversampler(c: &mut Criterion) {
+    let input = input();
+    let mut group = c.benchmark_group("oversampler");
+    group.throughput(Throughput::Elements(BLOCK as u64));
+    for factor in [2, 4] {
+        let mut up_buffer = vec![0.0f32; 2 * PHASE_TAPS];
+        let mut down_buffer = vec![0.0f32; 2 * factor * PHASE_TAPS];
+        let mut up = Upsampler::new(&mut up_buffer, factor);
+        let mut down = Downsampler::new(&mut down_buffer, factor);
+        let mut ys = vec![0.0; factor];
+        group.bench_with_input(BenchmarkId::from_parameter(factor), &input, |b, input| {
+            b.iter(|| {
+                for x in input {
+                    up.tick(*x, &mut ys);
+                    black_box(down.tick(&ys));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn limiter(c: &mut Criterion) {
+    let input = input();
+    let mut group = c.benchmark_group("limiter");
+    group.throughput(Throughput::Elements(BLOCK as u64));
+    let mut limiter = Limiter::new(0.25, 0.999);
+    group.bench_with_input("limiter", &input, |b, input| {
+        b.iter(|| {
+            for x in input {
+                black_box(x * limiter.tick(x.abs()));
+            }
+        })
+    });
+    group.bench_with_input("soft_clip", &input, |b, input| {
+        b.iter(|| {
+            for x in input {
+                black_box(soft_clip(*x, 0.25, 0.4));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    delay,
+    apf,
+    iir::<1>,
+    iir::<4>,
+    pitch_shifter,
+    reverser,
+    oversampler,
+    limiter
+);
+criterion_main!(benches);
@@ -0,0 +1,87 @@
+//! Throughput of full plate stereo processing at several block sizes and sample rates,
+//! for the generic plate by block and sample by sample and, with the `simd` feature, the SIMD one.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use oxide_plate_dsp::plate::*;
+
+const BLOCKS: [usize; 3] = [64, 512, 4096];
+
+const SAMPLE_RATES: [f64; 3] = [44100.0, 96000.0, 192000.0];
+
+/// Pre delay in seconds.
+const PREDELAY: f64 = 0.02;
+
+fn params(sample_rate: f64) -> PlateParams<f32> {
+    PlateParams {
+        predelay: (PREDELAY * sample_rate) as usize,
+        decay: 0.7,
+        decay_modulation: 8,
+        ..PlateParams::default()
+    }
+}
+
+fn input(len: usize) -> [Vec<f32>; 2] {
+    let noise = |seed: u32| {
+        (0..len as u32)
+            .map(|t| {
+                let x = t
+                    .wrapping_mul(1664525)
+                    .wrapping_add(seed)
+                    .wrapping_mul(22695477);
+                x as f32 / u32::MAX as f32 - 0.5
+            })
+            .collect()
+    };
+    [noise(1), noise(2)]
+}
+
+fn plate(c: &mut Criterion) {
+    for sample_rate in SAMPLE_RATES {
+        let params = params(sample_rate);
+        let mut group = c.benchmark_group(format!("plate/{sample_rate}"));
+        for block in BLOCKS {
+            let input = input(block);
+            let inputs = [input[0].as_slice(), input[1].as_slice()];
+            let mut output = [vec![0.0; block], vec![0.0; block]];
+            group.throughput(Throughput::Elements(block as u64));
+
+            let mut plate = Plate::new(sample_rate, params.predelay);
+            plate.set_params(params.clone());
+            group.bench_function(BenchmarkId::new("block", block), |b| {
+                b.iter(|| {
+                    let mut outputs = output.each_mut().map(Vec::as_mut_slice);
+                    plate.process_block(black_box(&inputs), &mut outputs);
+                })
+            });
+
+            let mut plate = Plate::new(sample_rate, params.predelay);
+            plate.set_params(params.clone());
+            group.bench_function(BenchmarkId::new("per_sample", block), |b| {
+                b.iter(|| {
+                    let [left, right] = &mut output;
+                    for (((x_left, x_right), left), right) in
+                        inputs[0].iter().zip(inputs[1]).zip(left).zip(right)
+                    {
+                        [*left, *right] = plate.process_2ch(black_box(&[*x_left, *x_right]));
+                    }
+                })
+            });
+
+            #[cfg(feature = "simd")]
+            {
+                let mut plate = SimdPlate::new(sample_rate, params.predelay);
+                plate.set_params(params.clone());
+                group.bench_function(BenchmarkId::new("simd", block), |b| {
+                    b.iter(|| {
+                        let mut outputs = output.each_mut().map(Vec::as_mut_slice);
+                        plate.process_block(black_box(&inputs), &mut outputs);
+                    })
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, plate);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+//! A plate in a static buffer, as firmware without a heap would run it.
+//!
+//! Built as a static library exposing a C interface, so that the whole plate is compiled
+//! for the target. The embedded workflow builds it for a Cortex-M4F:
+//!
+//! ```console
+//! cargo build --package oxide_plate_dsp --example embedded --no-default-features --target thumbv7em-none-eabihf
+//! ```
+
+#![cfg_attr(target_os = "none", no_std)]
+
+use core::ptr::addr_of_mut;
+use oxide_plate_dsp::plate::*;
+
+const SAMPLE_RATE: f64 = 48000.0;
+
+/// Longest pre delay, 10 ms.
+const MAX_PREDELAY: usize = 480;
+
+/// Without the reverse mode, whose buffers wouldn't fit into the RAM of most parts.
+const SIZE: usize = required_size(SAMPLE_RATE, MAX_PREDELAY, 0, SHIMMER);
+
+static mut BUFFERS: StaticPlateBuffers<f32, SIZE> = StaticPlateBuffers::new(0.0);
+
+static mut PLATE: Option<Plate<'static, f32>> = None;
+
+/// Builds the plate. Called once at start up, before [`plate_process`].
+#[no_mangle]
+pub extern "C" fn plate_init() {
+    // Only borrowed here, and this runs once.
+    let buffers = unsafe { &mut *addr_of_mut!(BUFFERS) };
+    let mut plate =
+        buffers.build_with_lengths(PlateLengths::new(SAMPLE_RATE), MAX_PREDELAY, 0, SHIMMER);
+    plate.set_params(PlateParams {
+        predelay: MAX_PREDELAY,
+        decay: 0.7,
+        ..PlateParams::default()
+    });
+    unsafe { *addr_of_mut!(PLATE) = Some(plate) };
+}
+
+/// Runs `len` mono samples through the plate into the left and right outputs.
+///
+/// # Safety
+/// Each pointer has to be valid for `len` samples, the outputs must not overlap,
+/// and no other call may run at the same time.
+#[no_mangle]
+pub unsafe extern "C" fn plate_process(
+    input: *const f32,
+    left: *mut f32,
+    right: *mut f32,
+    len: usize,
+) {
+    let Some(plate) = (*addr_of_mut!(PLATE)).as_mut() else {
+        return;
+    };
+    let input = core::slice::from_raw_parts(input, len);
+    let left = core::slice::from_raw_parts_mut(left, len);
+    let right = core::slice::from_raw_parts_mut(right, len);
+    plate.process_block(&[input], &mut [left, right]);
+}
+
+#[cfg(target_os = "none")]
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    loop {
+        core::hint::spin_loop();
+    }
+}
@@ -0,0 +1,327 @@
+//! Fixed-point samples for DSP chips without an FPU.
+//!
+//! [`Q15`] and [`Q31`] wrap the [`fixed`] types of the same format and implement the
+//! `num_traits` the instruments and [`Plate`](crate::plate::Plate) are generic over.
+//! All of their arithmetic saturates, so an overshooting all-pass filter, IIR or tank
+//! clips at full scale instead of wrapping around to the opposite sign.
+//!
+//! Neither format can hold `1.0`, so [`One::one`] and conversions of `1.0` and above
+//! saturate to the largest value, which is one LSB below.
+//!
+//! ```rust
+//! # use oxide_plate_dsp::{fixed_point::Q15, plate::*};
+//! let mut plate = Plate::<Q15>::new(48000.0, 0);
+//! plate.set_params(PlateParams::default());
+//! let [left, right] = plate.process_2ch(&[Q15::from_num(0.5)]);
+//! ```
+
+use crate::plate::PlateParams;
+use core::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+use fixed::{
+    types::{I1F15, I1F31},
+    RadixParseFixedError,
+};
+use num_traits::{FromPrimitive, Num, One, Signed, ToPrimitive, Zero};
+
+macro_rules! saturating {
+    ($(#[$attr:meta])* $name:ident, $fixed:ty) => {
+        $(#[$attr])*
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub $fixed);
+
+        impl $name {
+            pub const MIN: Self = Self(<$fixed>::MIN);
+            pub const MAX: Self = Self(<$fixed>::MAX);
+
+            /// Converts `x`, saturating values out of range and rounding to the nearest.
+            pub fn from_num(x: f64) -> Self {
+                Self(<$fixed>::saturating_from_num(x))
+            }
+
+            pub fn to_num(self) -> f64 {
+                self.0.to_num()
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0.saturating_add(rhs.0))
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0.saturating_sub(rhs.0))
+            }
+        }
+
+        impl Mul for $name {
+            type Output = Self;
+
+            fn mul(self, rhs: Self) -> Self {
+                Self(self.0.saturating_mul(rhs.0))
+            }
+        }
+
+        impl Div for $name {
+            type Output = Self;
+
+            fn div(self, rhs: Self) -> Self {
+                Self(self.0.saturating_div(rhs.0))
+            }
+        }
+
+        impl Rem for $name {
+            type Output = Self;
+
+            fn rem(self, rhs: Self) -> Self {
+                Self(self.0 % rhs.0)
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self(self.0.saturating_neg())
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl MulAssign for $name {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl DivAssign for $name {
+            fn div_assign(&mut self, rhs: Self) {
+                *self = *self / rhs;
+            }
+        }
+
+        impl RemAssign for $name {
+            fn rem_assign(&mut self, rhs: Self) {
+                *self = *self % rhs;
+            }
+        }
+
+        impl Zero for $name {
+            fn zero() -> Self {
+                Self(<$fixed>::ZERO)
+            }
+
+            fn is_zero(&self) -> bool {
+                self.0 == <$fixed>::ZERO
+            }
+        }
+
+        impl One for $name {
+            /// The largest value, as `1.0` is out of range.
+            fn one() -> Self {
+                Self::MAX
+            }
+        }
+
+        impl Num for $name {
+            type FromStrRadixErr = RadixParseFixedError;
+
+            fn from_str_radix(str: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+                match radix {
+                    2 => <$fixed>::from_str_binary(str),
+                    8 => <$fixed>::from_str_octal(str),
+                    10 => str.parse(),
+                    16 => <$fixed>::from_str_hex(str),
+                    _ => return Err(RadixParseFixedError::UnsupportedRadix),
+                }
+                .map(Self)
+                .map_err(RadixParseFixedError::ParseFixedError)
+            }
+        }
+
+        impl Signed for $name {
+            fn abs(&self) -> Self {
+                Self(self.0.saturating_abs())
+            }
+
+            fn abs_sub(&self, other: &Self) -> Self {
+                if self <= other {
+                    Self::zero()
+                } else {
+                    *self - *other
+                }
+            }
+
+            fn signum(&self) -> Self {
+                Self(self.0.saturating_signum())
+            }
+
+            fn is_positive(&self) -> bool {
+                self.0.is_positive()
+            }
+
+            fn is_negative(&self) -> bool {
+                self.0.is_negative()
+            }
+        }
+
+        impl FromPrimitive for $name {
+            fn from_i64(n: i64) -> Option<Self> {
+                Some(Self(<$fixed>::saturating_from_num(n)))
+            }
+
+            fn from_u64(n: u64) -> Option<Self> {
+                Some(Self(<$fixed>::saturating_from_num(n)))
+            }
+
+            fn from_f32(n: f32) -> Option<Self> {
+                Some(Self(<$fixed>::saturating_from_num(n)))
+            }
+
+            fn from_f64(n: f64) -> Option<Self> {
+                Some(Self::from_num(n))
+            }
+        }
+
+        impl ToPrimitive for $name {
+            fn to_i64(&self) -> Option<i64> {
+                self.0.checked_to_num()
+            }
+
+            fn to_u64(&self) -> Option<u64> {
+                self.0.checked_to_num()
+            }
+
+            fn to_f32(&self) -> Option<f32> {
+                Some(self.0.to_num())
+            }
+
+            fn to_f64(&self) -> Option<f64> {
+                Some(self.0.to_num())
+            }
+        }
+
+        impl Default for PlateParams<$name> {
+            fn default() -> Self {
+                Self {
+                    predelay: 1,
+                    bandwidth: $name::from_num(0.9995),
+                    input_diffusion_1: $name::from_num(0.750),
+                    input_diffusion_2: $name::from_num(0.625),
+                    decay_diffusion_1: $name::from_num(0.70),
+                    decay_diffusion_2: $name::from_num(0.50),
+                    damping: $name::from_num(0.0005),
+                    decay: $name::from_num(0.50),
+                    drive: $name::zero(),
+                    character: $name::zero(),
+                    decay_modulation: 0,
+                    shimmer: $name::zero(),
+                    shimmer_interval: 12,
+                    reverse: false,
+                    reverse_window: 16384,
+                }
+            }
+        }
+    };
+}
+
+saturating! {
+    /// Saturating Q15 sample: 1 sign bit and 15 fractional bits, in `[-1, 1)`.
+    Q15, I1F15
+}
+
+saturating! {
+    /// Saturating Q31 sample: 1 sign bit and 31 fractional bits, in `[-1, 1)`.
+    Q31, I1F31
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plate::*;
+
+    #[test]
+    fn saturation() {
+        let half = Q15::from_num(0.5);
+        assert_eq!(Q15::MAX, half + half + half);
+        assert_eq!(Q15::MIN, -half - half - half);
+        assert_eq!(Q15::MAX, -Q15::MIN);
+        assert_eq!(Q15::MAX, Q15::MIN * Q15::MIN);
+        assert_eq!(Q15::MAX, Q15::from_num(1.0));
+        assert_eq!(0.25, (half * half).to_num());
+        assert_eq!(Q31::MIN, Q31::from_num(-7.0));
+        assert_eq!(Some(Q31::MAX), Q31::from_i64(2));
+        assert_eq!(Ok(half), Q15::from_str_radix("0.1", 2));
+    }
+
+    fn impulse_response<T>(plate: &mut OwnedPlate<T>) -> Vec<f64>
+    where
+        T: Num + num_traits::NumAssign + Signed + FromPrimitive + ToPrimitive + Clone,
+    {
+        (0..20000)
+            .map(|t| {
+                let x = if t == 0 {
+                    T::from_f64(0.5)
+                } else {
+                    T::from_f64(0.0)
+                };
+                plate.process_2ch(&[x.unwrap()])[0].to_f64().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plate() {
+        let mut reference = Plate::<f64>::new(48000.0, 0);
+        reference.set_params(PlateParams::default());
+        let reference = impulse_response(&mut reference);
+
+        let mut q31 = Plate::<Q31>::new(48000.0, 0);
+        q31.set_params(PlateParams::default());
+        for (x, y) in reference.iter().zip(impulse_response(&mut q31)) {
+            assert!((x - y).abs() < 1e-6);
+        }
+
+        let mut q15 = Plate::<Q15>::new(48000.0, 0);
+        q15.set_params(PlateParams::default());
+        for (x, y) in reference.iter().zip(impulse_response(&mut q15)) {
+            assert!((x - y).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn clipping() {
+        let mut plate = Plate::<Q15>::new(48000.0, 0);
+        plate.set_params(PlateParams {
+            decay: Q15::from_num(0.99),
+            ..PlateParams::default()
+        });
+        let mut previous = [Q15::zero(); 2];
+        for t in 0..20000 {
+            let x = if t % 200 < 100 { Q15::MAX } else { Q15::MIN };
+            let y = plate.process_2ch(&[x, x]);
+            // Saturating keeps a full scale signal from wrapping into the opposite sign.
+            for (y, previous) in y.iter().zip(previous) {
+                assert!((y.to_num() - previous.to_num()).abs() < 1.5);
+            }
+            previous = y;
+        }
+    }
+}
@@ -0,0 +1,20 @@
+mod delay;
+mod filters;
+mod limiter;
+mod oversampler;
+mod pitch_shifter;
+mod reverser;
+mod saturator;
+
+pub use delay::Delay;
+pub use filters::APF;
+pub use filters::IIR;
+pub use limiter::soft_clip;
+pub use limiter::Limiter;
+pub use oversampler::Downsampler;
+pub use oversampler::Upsampler;
+pub use oversampler::PHASE_TAPS;
+pub use pitch_shifter::interval_to_ratio;
+pub use pitch_shifter::PitchShifter;
+pub use reverser::Reverser;
+pub use saturator::Saturator;
@@ -13,7 +13,7 @@ impl<'a, T> Delay<'a, T> {
     /// - If the buffer is empty.
     ///
     /// ```rust
-    /// # use plate::instruments::Delay;
+    /// # use oxide_plate_dsp::instruments::Delay;
     /// let mut buffer = [0.0; 8];
     /// let mut delay = Delay::new(&mut buffer);
     /// ```
@@ -28,7 +28,7 @@ impl<'a, T> Delay<'a, T> {
     /// The buffer will be overridden.
     ///
     /// ```rust
-    /// # use plate::instruments::Delay;
+    /// # use oxide_plate_dsp::instruments::Delay;
     /// let mut buffer = [0.0; 2];
     /// let mut delay = Delay::new(&mut buffer);
     ///
@@ -48,7 +48,7 @@ impl<'a, T> Delay<'a, T> {
     /// `delay = 1` means the time right after writing at.
     ///
     /// ```rust
-    /// # use plate::instruments::Delay;
+    /// # use oxide_plate_dsp::instruments::Delay;
     /// let mut buffer = [0.0; 2];
     /// let mut delay = Delay::new(&mut buffer);
     ///
@@ -62,6 +62,68 @@ impl<'a, T> Delay<'a, T> {
         &self.buffer[self.index(delay)]
     }
 
+    /// Reads what [`Delay::read`] returns with `delay` now and after each of the next writes,
+    /// one value per element of `ys`, in at most two contiguous copies.
+    ///
+    /// ## Panics
+    /// - If `ys` is longer than `delay`, as the later values would not be written yet.
+    ///
+    /// ```rust
+    /// # use oxide_plate_dsp::instruments::Delay;
+    /// let mut buffer = [0.0; 4];
+    /// let mut delay = Delay::new(&mut buffer);
+    /// delay.write(1.0);
+    /// delay.write(2.0);
+    ///
+    /// let mut ys = [0.0; 2];
+    /// delay.read_block(2.try_into().unwrap(), &mut ys);
+    /// assert_eq!([1.0, 2.0], ys);
+    /// ```
+    pub fn read_block(&self, delay: NonZeroUsize, ys: &mut [T])
+    where
+        T: Clone,
+    {
+        if ys.len() > delay.get() {
+            panic!("block is longer than the delay");
+        }
+        let mut start = self.index(delay);
+        let mut ys = ys;
+        while !ys.is_empty() {
+            let len = (self.buffer.len() - start).min(ys.len());
+            let (head, tail) = ys.split_at_mut(len);
+            head.clone_from_slice(&self.buffer[start..start + len]);
+            ys = tail;
+            start = 0;
+        }
+    }
+
+    /// Writes the values in order, as many calls of [`Delay::write`] would.
+    ///
+    /// ```rust
+    /// # use oxide_plate_dsp::instruments::Delay;
+    /// let mut buffer = [0.0; 2];
+    /// let mut delay = Delay::new(&mut buffer);
+    ///
+    /// delay.write_block(&[1.0, 2.0, 3.0]);
+    ///
+    /// assert_eq!(3.0, *delay.read(1.try_into().unwrap()));
+    /// assert_eq!(2.0, *delay.read(2.try_into().unwrap()));
+    /// ```
+    pub fn write_block(&mut self, xs: &[T])
+    where
+        T: Clone,
+    {
+        let buffer_len = self.buffer.len();
+        // Only the last values of a block longer than the buffer survive.
+        let skip = xs.len().saturating_sub(buffer_len);
+        let xs = &xs[skip..];
+        let start = (self.head + skip) % buffer_len;
+        let len = (buffer_len - start).min(xs.len());
+        self.buffer[start..start + len].clone_from_slice(&xs[..len]);
+        self.buffer[..xs.len() - len].clone_from_slice(&xs[len..]);
+        self.head = (start + xs.len()) % buffer_len;
+    }
+
     fn index(&self, delay: NonZeroUsize) -> usize {
         let buffer_len = self.buffer.len();
         match (self.head, delay.get() % buffer_len) {
@@ -132,6 +194,30 @@ mod tests {
         assert_eq!(4.0, *buffer.read(4.try_into().unwrap()));
     }
 
+    #[test]
+    fn block() {
+        let mut binding_1 = [0; 5];
+        let mut binding_2 = [0; 5];
+        let mut per_sample = Delay::new(&mut binding_1);
+        let mut block = Delay::new(&mut binding_2);
+        let delay = 3.try_into().unwrap();
+        for xs in [[1, 2, 3], [4, 5, 6], [7, 8, 9]] {
+            let expected: Vec<i32> = xs
+                .iter()
+                .map(|x| {
+                    let y = *per_sample.read(delay);
+                    per_sample.write(*x);
+                    y
+                })
+                .collect();
+            let mut ys = [0; 3];
+            block.read_block(delay, &mut ys);
+            block.write_block(&xs);
+            assert_eq!(expected, ys);
+            assert_eq!(per_sample, block);
+        }
+    }
+
     #[test]
     fn expand() {
         let mut buffer1 = [1, 2];
@@ -151,6 +237,6 @@ mod tests {
     #[test]
     fn from_vec() {
         let mut buffer = vec![0, 1, 2];
-        Delay::new(&mut buffer.as_mut());
+        Delay::new(buffer.as_mut());
     }
 }
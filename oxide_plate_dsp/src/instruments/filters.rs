@@ -21,16 +21,16 @@ where
     /// - If the buffer is empty.
     /// - If the buffer's length is less than 2.
     pub fn new(buffer: &'a mut [T]) -> APF<'a, T> {
-        return Self::with_params(buffer, 1.try_into().unwrap(), T::zero(), T::zero());
+        Self::with_params(buffer, 1.try_into().unwrap(), T::zero(), T::zero())
     }
 
     pub fn with_params(buffer: &'a mut [T], delay: NonZeroUsize, a: T, b: T) -> APF<'a, T> {
-        return APF {
+        APF {
             delay,
             a,
             b,
             delay_line: Delay::new(buffer),
-        };
+        }
     }
 
     pub fn set_params(&mut self, a: T, b: T, delay: NonZeroUsize) {
@@ -50,6 +50,45 @@ where
         self.delay_line.write(x);
         y
     }
+
+    /// Filters `xs` in place, as [`APF::tick`] would sample by sample,
+    /// using `z` to hold the delayed values.
+    ///
+    /// ## Panics
+    /// - If `xs` is longer than the delay, as the block would feed back into itself.
+    /// - If `z` is shorter than `xs`.
+    pub fn process_block(&mut self, xs: &mut [T], z: &mut [T]) {
+        let z = &mut z[..xs.len()];
+        self.delay_line.read_block(self.delay, z);
+        for (x, z) in xs.iter_mut().zip(z.iter()) {
+            *x = x.clone() - self.b.clone() * z.clone();
+        }
+        self.delay_line.write_block(xs);
+        for (x, z) in xs.iter_mut().zip(z.iter()) {
+            *x = self.a.clone() * x.clone() + z.clone();
+        }
+    }
+
+    /// Reads the delay line like [`APF::sample_buffer`], one value per element of `ys`,
+    /// as [`Delay::read_block`] does.
+    pub fn sample_block(&self, delay: NonZeroUsize, ys: &mut [T]) {
+        self.delay_line.read_block(delay, ys);
+    }
+
+    /// Returns the current delay in samples.
+    pub fn delay(&self) -> NonZeroUsize {
+        self.delay
+    }
+}
+
+impl<'a, T> APF<'a, T>
+where
+    T: Default + Clone,
+{
+    /// Clears the delay line with default values.
+    pub fn clear(&mut self) {
+        self.delay_line.clear();
+    }
 }
 
 /// Nth order IIR filter.
@@ -93,6 +132,16 @@ where
     }
 }
 
+impl<'a, T, const ORDER: usize> IIR<'a, T, ORDER>
+where
+    T: Default + Clone,
+{
+    /// Clears the state with default values.
+    pub fn clear(&mut self) {
+        self.z.fill(T::default());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,6 +154,22 @@ mod tests {
         assert_eq!(-1, apf.tick(1));
     }
 
+    #[test]
+    fn apf_block() {
+        let mut buffer_1 = [0.0; 4];
+        let mut buffer_2 = [0.0; 4];
+        let delay = 3.try_into().unwrap();
+        let mut per_sample = APF::with_params(&mut buffer_1, delay, 0.5, 0.5);
+        let mut block = APF::with_params(&mut buffer_2, delay, 0.5, 0.5);
+        let mut z = [0.0; 3];
+        for block_xs in [[1.0, 0.0, -1.0], [0.5, 2.0, 0.0], [0.0, 0.0, 0.0]] {
+            let expected = block_xs.map(|x| per_sample.tick(x));
+            let mut xs = block_xs;
+            block.process_block(&mut xs, &mut z);
+            assert_eq!(expected, xs);
+        }
+    }
+
     #[test]
     fn iir() {
         let mut buffer = [0.0; 1];
@@ -0,0 +1,114 @@
+use num_traits::Signed;
+
+/// Passes the signal unchanged below `knee` and bends it smoothly towards `ceiling` above it,
+/// so that the output never exceeds the ceiling.
+///
+/// ```rust
+/// # use oxide_plate_dsp::instruments::soft_clip;
+/// assert_eq!(0.25, soft_clip(0.25, 0.5, 1.0));
+/// assert!(soft_clip(100.0, 0.5, 1.0) < 1.0);
+/// assert_eq!(-soft_clip(2.0, 0.5, 1.0), soft_clip(-2.0, 0.5, 1.0));
+/// ```
+pub fn soft_clip<T>(x: T, knee: T, ceiling: T) -> T
+where
+    T: num_traits::Num + Signed + PartialOrd + Clone,
+{
+    let magnitude = x.abs();
+    if magnitude <= knee {
+        return x;
+    }
+    let over = magnitude - knee.clone();
+    let range = ceiling - knee.clone();
+    let y = knee + over.clone() / (T::one() + over / range);
+    x.signum() * y
+}
+
+/// Brickwall limiter with instant attack and exponential release.
+///
+/// The gain drops at once to keep the peak at the ceiling
+/// and recovers by `1 - release` of the remaining distance per sample.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Limiter<T> {
+    ceiling: T,
+    release: T,
+    gain: T,
+}
+
+impl<T> Limiter<T>
+where
+    T: num_traits::Num + PartialOrd + Clone,
+{
+    pub fn new(ceiling: T, release: T) -> Self {
+        Self {
+            ceiling,
+            release,
+            gain: T::one(),
+        }
+    }
+
+    pub fn set_params(&mut self, ceiling: T, release: T) {
+        self.ceiling = ceiling;
+        self.release = release;
+    }
+
+    /// Returns the gain to apply to a frame whose absolute peak is `peak`.
+    pub fn tick(&mut self, peak: T) -> T {
+        let target = if peak > self.ceiling {
+            self.ceiling.clone() / peak
+        } else {
+            T::one()
+        };
+        if target < self.gain {
+            self.gain = target;
+        } else {
+            let gain = self.gain.clone();
+            self.gain = gain.clone() + (T::one() - self.release.clone()) * (target - gain);
+        }
+        self.gain.clone()
+    }
+
+    /// Releases the gain reduction at once.
+    pub fn clear(&mut self) {
+        self.gain = T::one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soft_clip_bounds() {
+        let mut previous = 0.0;
+        for i in 0..1000 {
+            let x = i as f64 * 0.01;
+            let y = soft_clip(x, 0.5, 1.0);
+            assert!(y < 1.0);
+            assert!(y >= previous);
+            previous = y;
+        }
+        // The slope is continuous at the knee.
+        let slope = (soft_clip(0.5 + 1e-6, 0.5, 1.0) - 0.5) / 1e-6;
+        assert!((slope - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn limiter() {
+        let mut limiter = Limiter::new(1.0, 0.9);
+        assert_eq!(1.0, limiter.tick(0.5));
+
+        let gain = limiter.tick(4.0);
+        assert_eq!(0.25, gain);
+        assert!(4.0 * gain <= 1.0);
+
+        // Releasing towards 0.5 without overshooting the ceiling.
+        let gain = limiter.tick(2.0);
+        assert!(0.25 < gain && 2.0 * gain <= 1.0);
+
+        let mut gain = 0.0;
+        for _ in 0..200 {
+            gain = limiter.tick(0.1);
+        }
+        assert!((gain - 1.0).abs() < 1e-6);
+    }
+}
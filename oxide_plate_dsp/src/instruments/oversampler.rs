@@ -0,0 +1,544 @@
+/// Right half of the 2x kernel, from the center tap outwards.
+///
+/// A sinc low-pass at the lower rate's Nyquist frequency under a Kaiser window (β = 5),
+/// with each polyphase branch normalized to a gain of 1/2 at DC, so that interpolated
+/// samples don't ripple. The last tap is zero, but keeps the round trip latency
+/// a whole number of samples at the lower rate.
+const KERNEL_2: [f64; 17] = [
+    0.5,
+    0.315_921_628_845_563,
+    0.0,
+    -0.098_148_695_560_137_47,
+    0.0,
+    0.051_000_238_924_428_7,
+    0.0,
+    -0.029_116_891_337_413_915,
+    0.0,
+    0.016_499_487_402_155_684,
+    0.0,
+    -0.008_764_708_743_459_484,
+    0.0,
+    0.004_100_753_017_751_1,
+    0.0,
+    -0.001_491_812_548_887_688_5,
+    0.0,
+];
+
+/// Right half of the 4x kernel, designed like [`KERNEL_2`].
+const KERNEL_4: [f64; 33] = [
+    0.25,
+    0.224_789_657_541_779_82,
+    0.157_960_814_422_781_5,
+    0.073_631_042_758_255_67,
+    0.0,
+    -0.042_652_598_109_106_09,
+    -0.049_074_347_780_068_736,
+    -0.028_887_968_033_111_874,
+    0.0,
+    0.020_911_514_746_402_7,
+    0.025_500_119_462_214_35,
+    0.015_617_167_578_006_423,
+    0.0,
+    -0.011_816_350_426_397_875,
+    -0.014_558_445_668_706_958,
+    -0.008_957_318_261_375_02,
+    0.0,
+    0.006_748_405_458_559_929_5,
+    0.008_249_743_701_077_842,
+    0.005_019_268_127_445_725,
+    0.0,
+    -0.003_661_563_199_925_421,
+    -0.004_382_354_371_729_742,
+    -0.002_600_796_116_334_256,
+    0.0,
+    0.001_781_944_612_120_432_6,
+    0.002_050_376_508_875_55,
+    0.001_161_754_308_880_003_3,
+    0.0,
+    -0.000_704_502_154_771_141_3,
+    -0.000_745_906_274_443_844_2,
+    -0.000_379_658_830_429_081_3,
+    0.0,
+];
+
+/// Samples of the lower rate each polyphase branch spans.
+///
+/// As every sample is kept twice, an [`Upsampler`] needs a buffer of twice this length
+/// and a [`Downsampler`] one of `2 * factor` times this length.
+pub const PHASE_TAPS: usize = 17;
+
+/// Right half of the kernel for `factor`.
+///
+/// ## Panics
+/// - If the factor is not 1, 2 or 4.
+fn kernel(factor: usize) -> &'static [f64] {
+    match factor {
+        1 => &[1.0],
+        2 => &KERNEL_2,
+        4 => &KERNEL_4,
+        _ => panic!("unsupported factor"),
+    }
+}
+
+/// Taps of the longest kernel, the 4x one.
+const MAX_TAPS: usize = 2 * KERNEL_4.len() - 1;
+
+/// The whole kernel for `factor` scaled by `gain`, converted once instead of on every tick,
+/// and the number of its taps, after which the array is padded with zeros.
+///
+/// ## Panics
+/// - If the factor is not 1, 2 or 4.
+fn taps<T>(factor: usize, gain: f64) -> ([T; MAX_TAPS], usize)
+where
+    T: num_traits::FromPrimitive,
+{
+    let kernel = kernel(factor);
+    let center = kernel.len() - 1;
+    let taps = core::array::from_fn(|j| {
+        let tap = kernel.get(j.abs_diff(center)).copied().unwrap_or(0.0);
+        T::from_f64(gain * tap).unwrap()
+    });
+    (taps, 2 * center + 1)
+}
+
+/// Memory of a [`History`], borrowed or allocated by the instrument itself.
+#[derive(Debug, PartialEq, Eq)]
+enum Buffer<'a, T> {
+    Borrowed(&'a mut [T]),
+    #[cfg(feature = "alloc")]
+    Owned(alloc::boxed::Box<[T]>),
+}
+
+impl<'a, T> core::ops::Deref for Buffer<'a, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Buffer::Borrowed(buffer) => buffer,
+            #[cfg(feature = "alloc")]
+            Buffer::Owned(buffer) => buffer,
+        }
+    }
+}
+
+impl<'a, T> core::ops::DerefMut for Buffer<'a, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        match self {
+            Buffer::Borrowed(buffer) => buffer,
+            #[cfg(feature = "alloc")]
+            Buffer::Owned(buffer) => buffer,
+        }
+    }
+}
+
+/// The latest samples, newest first.
+///
+/// Every sample is written twice, half the buffer apart,
+/// so that the latest ones are contiguous and the kernel runs over a plain slice
+/// instead of wrapping around on every tap.
+#[derive(Debug, PartialEq, Eq)]
+struct History<'a, T> {
+    head: usize,
+    buffer: Buffer<'a, T>,
+}
+
+impl<'a, T> History<'a, T>
+where
+    T: Clone,
+{
+    fn new(buffer: Buffer<'a, T>) -> Self {
+        History { head: 0, buffer }
+    }
+
+    /// Number of samples kept.
+    fn len(&self) -> usize {
+        self.buffer.len() / 2
+    }
+
+    fn write(&mut self, x: T) {
+        let len = self.len();
+        self.head = if self.head == 0 {
+            len - 1
+        } else {
+            self.head - 1
+        };
+        self.buffer[self.head + len] = x.clone();
+        self.buffer[self.head] = x;
+    }
+
+    /// The latest samples, `latest()[k]` having been written `k` samples ago.
+    fn latest(&self) -> &[T] {
+        &self.buffer[self.head..self.head + self.len()]
+    }
+}
+
+impl<'a, T> History<'a, T>
+where
+    T: Default + Clone,
+{
+    fn clear(&mut self) {
+        self.buffer.fill(T::default());
+    }
+}
+
+/// Raises the sample rate by 1, 2 or 4 with a polyphase FIR interpolator.
+///
+/// Each output sample of a tick is computed by one branch of the low-pass kernel,
+/// which skips the zeros that would be stuffed between the input samples.
+#[derive(Debug, PartialEq)]
+pub struct Upsampler<'a, T> {
+    history: History<'a, T>,
+    factor: usize,
+    taps: [T; MAX_TAPS],
+    length: usize,
+}
+
+impl<'a, T> Upsampler<'a, T>
+where
+    T: num_traits::Num + num_traits::FromPrimitive + Clone,
+{
+    /// Creates a new instance raising the sample rate by `factor`.
+    ///
+    /// ## Panics
+    /// - If the factor is not 1, 2 or 4.
+    /// - If the buffer's length is less than twice [`PHASE_TAPS`].
+    ///
+    /// ```rust
+    /// # use oxide_plate_dsp::instruments::{Upsampler, PHASE_TAPS};
+    /// let mut buffer = [0.0; 2 * PHASE_TAPS];
+    /// let mut upsampler = Upsampler::new(&mut buffer, 2);
+    ///
+    /// let mut ys = [0.0; 2];
+    /// upsampler.tick(1.0, &mut ys);
+    /// ```
+    pub fn new(buffer: &'a mut [T], factor: usize) -> Upsampler<'a, T> {
+        Self::with_buffer(Buffer::Borrowed(buffer), factor)
+    }
+
+    fn with_buffer(buffer: Buffer<'a, T>, factor: usize) -> Upsampler<'a, T> {
+        // Compensates for the energy of the stuffed zeros.
+        let (taps, length) = taps(factor, factor as f64);
+        if buffer.len() < 2 * PHASE_TAPS {
+            panic!("buffer is too short");
+        }
+        Upsampler {
+            history: History::new(buffer),
+            factor,
+            taps,
+            length,
+        }
+    }
+
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    /// Returns the delay of the interpolated signal, in samples at the higher rate.
+    pub fn latency(&self) -> usize {
+        self.length / 2
+    }
+
+    /// Writes the `factor` samples interpolated up to `x` into `ys`.
+    ///
+    /// ## Panics
+    /// - If the length of `ys` differs from the factor.
+    pub fn tick(&mut self, x: T, ys: &mut [T]) {
+        if ys.len() != self.factor {
+            panic!("expected {} samples", self.factor);
+        }
+        self.history.write(x);
+        let latest = self.history.latest();
+        for (phase, y) in ys.iter_mut().enumerate() {
+            *y = self.taps[phase..self.length]
+                .iter()
+                .step_by(self.factor)
+                .zip(latest)
+                .map(|(tap, x)| tap.clone() * x.clone())
+                .fold(T::zero(), |acc, x| acc + x);
+        }
+    }
+
+    /// Switches to another factor and clears the history.
+    ///
+    /// ## Panics
+    /// - If the factor is not 1, 2 or 4.
+    pub fn set_factor(&mut self, factor: usize)
+    where
+        T: Default,
+    {
+        (self.taps, self.length) = taps(factor, factor as f64);
+        self.factor = factor;
+        self.history.clear();
+    }
+}
+
+impl<'a, T> Upsampler<'a, T>
+where
+    T: Default + Clone,
+{
+    /// Clears the history with default values.
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Upsampler<'static, T>
+where
+    T: num_traits::Num + num_traits::FromPrimitive + Clone,
+{
+    /// Creates a new instance like [`Upsampler::new`],
+    /// with a buffer of its own that is freed along with it.
+    ///
+    /// ## Panics
+    /// - If the factor is not 1, 2 or 4.
+    pub fn allocate(factor: usize) -> Self {
+        let buffer = alloc::vec![T::zero(); 2 * PHASE_TAPS];
+        Self::with_buffer(Buffer::Owned(buffer.into_boxed_slice()), factor)
+    }
+}
+
+/// Lowers the sample rate by 1, 2 or 4 with a polyphase FIR decimator.
+///
+/// Only every `factor`-th sample of the low-passed signal is computed.
+#[derive(Debug, PartialEq)]
+pub struct Downsampler<'a, T> {
+    history: History<'a, T>,
+    factor: usize,
+    taps: [T; MAX_TAPS],
+    length: usize,
+    /// Highest factor the buffer is long enough for.
+    max_factor: usize,
+}
+
+impl<'a, T> Downsampler<'a, T>
+where
+    T: num_traits::Num + num_traits::FromPrimitive + Clone,
+{
+    /// Creates a new instance lowering the sample rate by `factor`.
+    ///
+    /// ## Panics
+    /// - If the factor is not 1, 2 or 4.
+    /// - If the buffer's length is less than `2 * factor` times [`PHASE_TAPS`].
+    pub fn new(buffer: &'a mut [T], factor: usize) -> Downsampler<'a, T> {
+        Self::with_buffer(Buffer::Borrowed(buffer), factor)
+    }
+
+    fn with_buffer(buffer: Buffer<'a, T>, factor: usize) -> Downsampler<'a, T> {
+        let (taps, length) = taps(factor, 1.0);
+        if buffer.len() < 2 * factor * PHASE_TAPS {
+            panic!("buffer is too short");
+        }
+        Downsampler {
+            max_factor: buffer.len() / (2 * PHASE_TAPS),
+            history: History::new(buffer),
+            factor,
+            taps,
+            length,
+        }
+    }
+
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    /// Returns the delay of the decimated signal, in samples at the higher rate.
+    pub fn latency(&self) -> usize {
+        self.length / 2
+    }
+
+    /// Takes `factor` consecutive samples and returns one sample at the lower rate.
+    ///
+    /// ## Panics
+    /// - If the length of `xs` differs from the factor.
+    pub fn tick(&mut self, xs: &[T]) -> T {
+        if xs.len() != self.factor {
+            panic!("expected {} samples", self.factor);
+        }
+        for x in xs {
+            self.history.write(x.clone());
+        }
+        // Aligned to the first of `xs`, so that the round trip latency is whole.
+        self.taps[..self.length]
+            .iter()
+            .zip(&self.history.latest()[self.factor - 1..])
+            .map(|(tap, x)| tap.clone() * x.clone())
+            .fold(T::zero(), |acc, x| acc + x)
+    }
+
+    /// Switches to another factor and clears the history.
+    ///
+    /// ## Panics
+    /// - If the factor is not 1, 2 or 4.
+    /// - If the buffer's length is less than `2 * factor` times [`PHASE_TAPS`].
+    pub fn set_factor(&mut self, factor: usize)
+    where
+        T: Default,
+    {
+        let (taps, length) = taps(factor, 1.0);
+        if factor > self.max_factor {
+            panic!("buffer is too short");
+        }
+        (self.taps, self.length) = (taps, length);
+        self.factor = factor;
+        self.history.clear();
+    }
+}
+
+impl<'a, T> Downsampler<'a, T>
+where
+    T: Default + Clone,
+{
+    /// Clears the history with default values.
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Downsampler<'static, T>
+where
+    T: num_traits::Num + num_traits::FromPrimitive + Clone,
+{
+    /// Creates a new instance like [`Downsampler::new`], with a buffer of its own
+    /// long enough for factors up to `max_factor` and freed along with it.
+    ///
+    /// ## Panics
+    /// - If the factor is not 1, 2 or 4.
+    /// - If the factor is higher than `max_factor`.
+    pub fn allocate(max_factor: usize, factor: usize) -> Self {
+        let buffer = alloc::vec![T::zero(); 2 * max_factor * PHASE_TAPS];
+        Self::with_buffer(Buffer::Owned(buffer.into_boxed_slice()), factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(factor: usize, xs: &[f64]) -> Vec<f64> {
+        let mut up_buffer = [0.0; 2 * PHASE_TAPS];
+        let mut down_buffer = vec![0.0; 2 * factor * PHASE_TAPS];
+        let mut up = Upsampler::new(&mut up_buffer, factor);
+        let mut down = Downsampler::new(&mut down_buffer, factor);
+        let mut ys = vec![0.0; factor];
+        xs.iter()
+            .map(|x| {
+                up.tick(*x, &mut ys);
+                down.tick(&ys)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn passthrough() {
+        let xs: Vec<f64> = (0..64).map(|t| (t as f64 * 0.3).sin()).collect();
+        assert_eq!(xs, round_trip(1, &xs));
+    }
+
+    #[test]
+    fn latency() {
+        for factor in [2, 4] {
+            let mut buffer = [0.0; 2 * PHASE_TAPS];
+            let up = Upsampler::<f64>::new(&mut buffer, factor);
+            let latency = 2 * up.latency() / factor;
+            assert_eq!(PHASE_TAPS - 1, latency);
+
+            let mut xs = vec![0.0; 64];
+            xs[0] = 1.0;
+            let ys = round_trip(factor, &xs);
+            let peak = (0..ys.len())
+                .max_by(|a, b| ys[*a].abs().total_cmp(&ys[*b].abs()))
+                .unwrap();
+            assert_eq!(latency, peak);
+        }
+    }
+
+    #[test]
+    fn dc() {
+        for factor in [2, 4] {
+            let mut buffer = [0.0; 2 * PHASE_TAPS];
+            let mut up = Upsampler::new(&mut buffer, factor);
+            let mut ys = vec![0.0f64; factor];
+            for _ in 0..PHASE_TAPS {
+                up.tick(1.0, &mut ys);
+            }
+            assert!(ys.iter().all(|y| (y - 1.0).abs() < 1e-12));
+
+            let ys = round_trip(factor, &[1.0; 64]);
+            assert!((ys[63] - 1.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn alias_rejection() {
+        for factor in [2, 4] {
+            let mut buffer = vec![0.0; 2 * factor * PHASE_TAPS];
+            let mut down = Downsampler::new(&mut buffer, factor);
+            // Above the lower rate's Nyquist frequency, where it would fold back.
+            let frequency = 0.65 / factor as f64;
+            let ys: Vec<f64> = (0..256)
+                .map(|t| {
+                    let xs: Vec<f64> = (0..factor)
+                        .map(|i| {
+                            let t = (factor * t + i) as f64;
+                            (core::f64::consts::TAU * frequency * t).sin()
+                        })
+                        .collect();
+                    down.tick(&xs)
+                })
+                .collect();
+            assert!(ys[PHASE_TAPS..].iter().all(|y| y.abs() < 0.01));
+        }
+    }
+
+    #[test]
+    fn set_factor() {
+        let mut up_buffer = [0.0; 2 * PHASE_TAPS];
+        let mut down_buffer = [0.0; 8 * PHASE_TAPS];
+        let mut up = Upsampler::new(&mut up_buffer, 1);
+        let mut down = Downsampler::new(&mut down_buffer, 1);
+        up.set_factor(4);
+        down.set_factor(4);
+
+        let mut xs = vec![0.0; 64];
+        xs[0] = 1.0;
+        let mut ys = [0.0; 4];
+        let ys: Vec<f64> = xs
+            .iter()
+            .map(|x| {
+                up.tick(*x, &mut ys);
+                down.tick(&ys)
+            })
+            .collect();
+        assert_eq!(round_trip(4, &xs), ys);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn allocate() {
+        let mut up = Upsampler::allocate(1);
+        let mut down = Downsampler::allocate(4, 1);
+        up.set_factor(4);
+        down.set_factor(4);
+
+        let mut xs = vec![0.0; 64];
+        xs[0] = 1.0;
+        let mut ys = [0.0; 4];
+        let ys: Vec<f64> = xs
+            .iter()
+            .map(|x| {
+                up.tick(*x, &mut ys);
+                down.tick(&ys)
+            })
+            .collect();
+        assert_eq!(round_trip(4, &xs), ys);
+    }
+
+    #[test]
+    #[should_panic]
+    fn unsupported_factor() {
+        let mut buffer = [0.0; 6 * PHASE_TAPS];
+        Downsampler::<f64>::new(&mut buffer, 3);
+    }
+}
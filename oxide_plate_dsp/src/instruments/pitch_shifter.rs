@@ -0,0 +1,158 @@
+use core::num::NonZeroUsize;
+
+use crate::instruments::Delay;
+
+/// Frequency ratios of the twelve semitones within an octave.
+const SEMITONES: [f64; 12] = [
+    1.0,
+    1.059_463_094_359_295_3,
+    1.122_462_048_309_373,
+    1.189_207_115_002_721,
+    1.259_921_049_894_873_2,
+    1.334_839_854_170_034_4,
+    core::f64::consts::SQRT_2,
+    1.498_307_076_876_681_5,
+    1.587_401_051_968_199_4,
+    1.681_792_830_507_429,
+    1.781_797_436_280_678_6,
+    1.887_748_625_363_386_8,
+];
+
+/// Converts an interval in semitones into a frequency ratio.
+///
+/// ```rust
+/// # use oxide_plate_dsp::instruments::interval_to_ratio;
+/// assert_eq!(2.0, interval_to_ratio(12));
+/// assert_eq!(0.5, interval_to_ratio(-12));
+/// ```
+pub fn interval_to_ratio(semitones: isize) -> f64 {
+    let mut ratio = SEMITONES[semitones.rem_euclid(12) as usize];
+    let octaves = semitones.div_euclid(12);
+    for _ in 0..octaves.unsigned_abs() {
+        if octaves > 0 {
+            ratio *= 2.0;
+        } else {
+            ratio /= 2.0;
+        }
+    }
+    ratio
+}
+
+/// Delay-line pitch shifter.
+///
+/// Two read heads sweep across the delay line half a window apart,
+/// each faded by a triangular window so that the jump back to the start is silent.
+#[derive(Debug, PartialEq)]
+pub struct PitchShifter<'a, T> {
+    delay_line: Delay<'a, T>,
+    window: f64,
+    phase: f64,
+    step: f64,
+}
+
+impl<'a, T> PitchShifter<'a, T>
+where
+    T: num_traits::Num + num_traits::FromPrimitive + Clone,
+{
+    /// Creates a new instance passing the signal through unshifted.
+    ///
+    /// ## Panics
+    /// - If the buffer's length is less than 3.
+    pub fn new(buffer: &'a mut [T]) -> PitchShifter<'a, T> {
+        if buffer.len() < 3 {
+            panic!("buffer is too short");
+        }
+        PitchShifter {
+            window: (buffer.len() - 2) as f64,
+            delay_line: Delay::new(buffer),
+            phase: 0.0,
+            step: 0.0,
+        }
+    }
+
+    /// Sets the pitch shift as a frequency ratio, `2.0` being an octave up.
+    pub fn set_ratio(&mut self, ratio: f64) {
+        self.step = (1.0 - ratio) / self.window;
+    }
+
+    pub fn tick(&mut self, x: T) -> T {
+        self.delay_line.write(x);
+
+        self.phase += self.step;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+        } else if self.phase < 0.0 {
+            self.phase += 1.0;
+        }
+
+        let mut other = self.phase + 0.5;
+        if other >= 1.0 {
+            other -= 1.0;
+        }
+
+        self.head(self.phase) + self.head(other)
+    }
+
+    fn head(&self, phase: f64) -> T {
+        let gain = if phase < 0.5 {
+            2.0 * phase
+        } else {
+            2.0 - 2.0 * phase
+        };
+        let delay = 1.0 + phase * self.window;
+        let index = delay as usize;
+        let fraction = delay - index as f64;
+
+        let near = self.delay_line.read(NonZeroUsize::new(index).unwrap());
+        let far = self.delay_line.read(NonZeroUsize::new(index + 1).unwrap());
+        let near = T::from_f64(gain * (1.0 - fraction)).unwrap() * near.clone();
+        let far = T::from_f64(gain * fraction).unwrap() * far.clone();
+        near + far
+    }
+}
+
+impl<'a, T> PitchShifter<'a, T>
+where
+    T: Default + Clone,
+{
+    /// Clears the delay line with default values.
+    pub fn clear(&mut self) {
+        self.delay_line.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals() {
+        assert_eq!(1.0, interval_to_ratio(0));
+        assert_eq!(4.0, interval_to_ratio(24));
+        assert_eq!(0.25, interval_to_ratio(-24));
+        assert_eq!(1.498_307_076_876_681_5, interval_to_ratio(7));
+    }
+
+    #[test]
+    fn unison() {
+        let mut buffer = [0.0; 10];
+        let mut shifter = PitchShifter::new(&mut buffer);
+        shifter.set_ratio(1.0);
+        for _ in 0..32 {
+            shifter.tick(1.0);
+        }
+        assert!((shifter.tick(1.0) - 1.0f64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn octave_up() {
+        let mut buffer = [0.0; 66];
+        let mut shifter = PitchShifter::new(&mut buffer);
+        shifter.set_ratio(2.0);
+        // The phase sweeps the whole window once every `window / (ratio - 1)` samples.
+        for _ in 0..64 {
+            shifter.tick(1.0);
+        }
+        assert_eq!(0.0, shifter.phase);
+    }
+}
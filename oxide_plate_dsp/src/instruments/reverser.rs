@@ -0,0 +1,137 @@
+use core::num::NonZeroUsize;
+
+use crate::instruments::Delay;
+
+/// Plays the signal back reversed in fixed windows.
+///
+/// Two read heads run half a window apart and are faded with triangular windows,
+/// so consecutive windows crossfade into each other.
+/// The output lags the input by one window.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Reverser<'a, T> {
+    delay_line: Delay<'a, T>,
+    window: usize,
+    max_window: usize,
+    position: usize,
+}
+
+impl<'a, T> Reverser<'a, T>
+where
+    T: num_traits::Num + num_traits::FromPrimitive + Clone,
+{
+    /// Creates a new instance with the longest window the buffer can hold.
+    ///
+    /// ## Panics
+    /// - If the buffer's length is less than 4.
+    pub fn new(buffer: &'a mut [T]) -> Reverser<'a, T> {
+        if buffer.len() < 4 {
+            panic!("buffer is too short");
+        }
+        Reverser {
+            window: buffer.len() / 2,
+            max_window: buffer.len() / 2,
+            delay_line: Delay::new(buffer),
+            position: 0,
+        }
+    }
+
+    /// Sets the window length, clamped to half of the buffer.
+    ///
+    /// ## Panics
+    /// - If the window is shorter than 2 samples.
+    pub fn set_window(&mut self, window: NonZeroUsize) {
+        if window.get() < 2 {
+            panic!("window is too short");
+        }
+        let window = window.get().min(self.max_window());
+        if window != self.window {
+            self.window = window;
+            self.position %= window;
+        }
+    }
+
+    /// Returns the window length, which is also the latency of this instrument.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Returns the longest window the buffer can hold.
+    pub fn max_window(&self) -> usize {
+        self.max_window
+    }
+
+    pub fn tick(&mut self, x: T) -> T {
+        self.delay_line.write(x);
+
+        let other = (self.position + self.window / 2) % self.window;
+        let y = self.head(self.position) + self.head(other);
+
+        self.position = (self.position + 1) % self.window;
+        y
+    }
+
+    fn head(&self, position: usize) -> T {
+        let window = self.window as f64;
+        let phase = position as f64 / window;
+        let gain = if phase < 0.5 {
+            2.0 * phase
+        } else {
+            2.0 - 2.0 * phase
+        };
+        // The sample written `position` samples before the window began.
+        let delay = NonZeroUsize::new(2 * position + 2).unwrap();
+        T::from_f64(gain).unwrap() * self.delay_line.read(delay).clone()
+    }
+}
+
+impl<'a, T> Reverser<'a, T>
+where
+    T: Default + Clone,
+{
+    /// Clears the delay line with default values.
+    pub fn clear(&mut self) {
+        self.delay_line.clear();
+        self.position = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impulse() {
+        let mut buffer = [0.0; 8];
+        let mut reverser = Reverser::new(&mut buffer);
+        reverser.set_window(4.try_into().unwrap());
+        let ys: Vec<f64> = (0..12)
+            .map(|t| reverser.tick(if t == 0 { 1.0 } else { 0.0 }))
+            .collect();
+        // The first sample of a window comes back at the end of the next one,
+        // half from each head.
+        assert_eq!(
+            vec![0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0],
+            ys
+        );
+    }
+
+    #[test]
+    fn constant_gain() {
+        let mut buffer = [0.0; 16];
+        let mut reverser = Reverser::new(&mut buffer);
+        for _ in 0..16 {
+            reverser.tick(1.0);
+        }
+        for _ in 0..16 {
+            assert_eq!(1.0, reverser.tick(1.0));
+        }
+    }
+
+    #[test]
+    fn clamp_window() {
+        let mut buffer = [0.0; 8];
+        let mut reverser = Reverser::new(&mut buffer);
+        reverser.set_window(100.try_into().unwrap());
+        assert_eq!(4, reverser.window());
+    }
+}
@@ -0,0 +1,192 @@
+use num_traits::Signed;
+
+/// Soft saturation for feedback loops, divided by its RMS gain at the current level
+/// so that the loop gain, and with it the decay, stays as it was.
+///
+/// ```rust
+/// # use oxide_plate_dsp::instruments::Saturator;
+/// let mut saturator = Saturator::new(4.0f64, 0.5, 0.999);
+/// assert_eq!(0.0, saturator.tick(0.0));
+/// assert!((saturator.tick(1e-6) - 1e-6).abs() < 1e-9);
+/// assert!(saturator.shape(100.0) < 1.0);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Saturator<T> {
+    drive: T,
+    /// Drive of the positive half.
+    positive: T,
+    /// Drive of the negative half.
+    negative: T,
+    smoothing: T,
+    /// Mean the curve adds to the signal.
+    offset: T,
+    /// Power going into the curve.
+    input: T,
+    /// Power coming out of the curve.
+    output: T,
+    /// Square root of `input / output`, refined by a Newton step per sample.
+    gain: T,
+    /// Whether `T` can hold the gain, which saturating fixed-point types can't.
+    compensate: bool,
+}
+
+impl<T> Saturator<T>
+where
+    T: num_traits::Num + Signed + Clone,
+{
+    pub fn new(drive: T, character: T, smoothing: T) -> Self {
+        let mut saturator = Self {
+            drive: T::zero(),
+            positive: T::zero(),
+            negative: T::zero(),
+            smoothing: T::zero(),
+            offset: T::zero(),
+            input: T::zero(),
+            output: T::zero(),
+            gain: T::one(),
+            compensate: T::one() + T::one() != T::one(),
+        };
+        saturator.set_params(drive, character, smoothing);
+        saturator
+    }
+
+    /// Sets the drive, the asymmetry between the halves,
+    /// from 0 for symmetric to 1 for saturating the positive half only,
+    /// and how slowly the compensation follows the level.
+    pub fn set_params(&mut self, drive: T, character: T, smoothing: T) {
+        self.positive = drive.clone() * (T::one() + character.clone());
+        self.negative = drive.clone() * (T::one() - character);
+        self.drive = drive;
+        self.smoothing = smoothing;
+    }
+
+    /// A `drive` of zero leaves the signal bit-identical.
+    pub fn is_bypassed(&self) -> bool {
+        self.drive.is_zero()
+    }
+
+    /// The curve `x / (1 + drive * |x|)` alone, without the compensation. Never amplifies.
+    pub fn shape(&self, x: T) -> T {
+        let drive = if x.is_negative() {
+            self.negative.clone()
+        } else {
+            self.positive.clone()
+        };
+        x.clone() / (T::one() + drive * x.abs())
+    }
+
+    /// Shapes `x` and compensates the gain the curve lost, or returns the curve alone
+    /// for types that can't exceed 1, such as `Q15` and `Q31`.
+    pub fn tick(&mut self, x: T) -> T {
+        if self.is_bypassed() {
+            return x;
+        }
+        let y = self.shape(x.clone());
+        if !self.compensate {
+            return y;
+        }
+        // The asymmetric halves add an offset, which would otherwise be compensated as level.
+        // The power in and out of the curve is followed by `1 - smoothing` per sample,
+        // and their ratio's square root is the RMS gain to divide by.
+        let follow =
+            |state: T, x: T| state.clone() + (T::one() - self.smoothing.clone()) * (x - state);
+        self.offset = follow(self.offset.clone(), y.clone() - x.clone());
+        let y = y - self.offset.clone();
+        self.input = follow(self.input.clone(), x.clone() * x);
+        self.output = follow(self.output.clone(), y.clone() * y.clone());
+        if !self.output.is_zero() {
+            let gain = self.gain.clone();
+            let two = T::one() + T::one();
+            self.gain = (gain.clone() + self.input.clone() / (self.output.clone() * gain)) / two;
+        }
+        self.gain.clone() * y
+    }
+}
+
+impl<T> Saturator<T>
+where
+    T: Default + num_traits::One,
+{
+    /// Forgets the level, so that the compensation starts over at unity.
+    pub fn clear(&mut self) {
+        self.offset = T::default();
+        self.input = T::default();
+        self.output = T::default();
+        self.gain = T::one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unity_gain() {
+        for (drive, character) in [(0.5, 0.0), (4.0, 0.0), (4.0, 1.0), (16.0, 0.3)] {
+            let mut saturator = Saturator::new(drive, character, 0.999);
+            assert_eq!(0.0, saturator.tick(0.0));
+            for x in [1e-6, -1e-6] {
+                assert!((saturator.tick(x) - x).abs() < 1e-4 * x.abs());
+            }
+        }
+
+        let mut saturator = Saturator::new(0.0, 1.0, 0.999);
+        assert!(saturator.is_bypassed());
+        assert_eq!(3.0, saturator.tick(3.0));
+    }
+
+    #[test]
+    fn bounds() {
+        let saturator = Saturator::new(4.0, 0.5, 0.999);
+        let mut previous = saturator.shape(-100.0);
+        for i in -1000..1000 {
+            let x = i as f64 * 0.1;
+            let y = saturator.shape(x);
+            assert!(y >= previous);
+            assert!(y.abs() <= x.abs());
+            previous = y;
+        }
+        // The positive half is driven harder.
+        assert!(saturator.shape(1.0) < -saturator.shape(-1.0));
+        assert!(saturator.shape(100.0) < 1.0 / 6.0);
+        assert!(saturator.shape(-100.0) > -1.0 / 2.0);
+    }
+
+    #[test]
+    fn compensation() {
+        for (drive, character) in [(4.0, 0.0), (16.0, 0.5)] {
+            let mut saturator = Saturator::new(drive, character, 0.999);
+            let (mut input, mut output, mut shaped) = (0.0, 0.0, 0.0);
+            for t in 0..20000 {
+                let x = ((t * 7919) % 101) as f64 / 50.0 - 1.0;
+                let y = saturator.tick(x);
+                if t >= 10000 {
+                    input += x * x;
+                    output += y * y;
+                    shaped += saturator.shape(x).powi(2);
+                }
+            }
+            // Loud signals keep their level, though the curve alone loses most of it.
+            assert!((output / input - 1.0).abs() < 0.05);
+            assert!(shaped < 0.5 * input);
+        }
+
+        let mut saturator = Saturator::new(4.0, 0.0, 0.999);
+        saturator.tick(1.0);
+        saturator.clear();
+        assert_eq!(Saturator::new(4.0, 0.0, 0.999), saturator);
+    }
+
+    #[cfg(feature = "fixed")]
+    #[test]
+    fn fixed_point() {
+        use crate::fixed_point::Q15;
+
+        // Q15 can't hold a gain above 1, so only the curve is applied.
+        let mut saturator = Saturator::new(Q15::from_num(0.5), Q15::from_num(0.5), Q15::MAX);
+        for t in 0..1000 {
+            let x = Q15::from_num(((t * 7919) % 101) as f64 / 50.0 - 1.0);
+            assert_eq!(saturator.shape(x), saturator.tick(x));
+        }
+    }
+}
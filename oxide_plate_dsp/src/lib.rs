@@ -0,0 +1,29 @@
+//! The DSP core of oxide plate: the instruments and the plate reverb built from them.
+//!
+//! The crate is `no_std`. Every instrument can borrow its memory, so buffers can live
+//! in statics on targets without a heap. Only the constructors that own their buffers
+//! allocate, which need `alloc`: [`Upsampler::allocate`](instruments::Upsampler::allocate),
+//! [`Downsampler::allocate`](instruments::Downsampler::allocate),
+//! [`PlateArena`](plate::PlateArena), and [`OwnedPlate`](plate::OwnedPlate)
+//! with [`Plate::new`](plate::Plate::new).
+//!
+//! ## Features
+//! - `alloc`: the allocating constructors above.
+//! - `std` (default): implies `alloc`, and adds [`wav`] for reading and writing WAV files
+//!   and rendering impulse responses.
+//! - `simd`: implies `alloc`, and adds `SimdPlate`, which runs the two tank halves
+//!   in SIMD lanes, and its allocated buffers.
+//! - `fixed`: saturating Q15 and Q31 samples in [`fixed_point`].
+
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+// Tests allocate their plates whatever the features.
+#[cfg(any(test, feature = "alloc"))]
+extern crate alloc;
+
+#[cfg(feature = "fixed")]
+pub mod fixed_point;
+pub mod instruments;
+pub mod plate;
+#[cfg(feature = "std")]
+pub mod wav;
@@ -0,0 +1,1455 @@
+use crate::instruments::*;
+use core::{fmt::Debug, marker::PhantomData, num::NonZeroUsize};
+
+#[cfg(any(test, feature = "alloc"))]
+mod arena;
+#[cfg(feature = "simd")]
+mod simd;
+mod static_buffers;
+
+#[cfg(any(test, feature = "alloc"))]
+pub use arena::{OwnedPlate, PlateArena};
+#[cfg(feature = "simd")]
+pub use simd::{OwnedSimdPlate, SimdPlate, SimdPlateBuffers};
+pub use static_buffers::{required_size, StaticPlateBuffers};
+
+pub struct PlateBuffers<T, V> {
+    pub predelay: T,
+    pub prefilter: T,
+
+    pub input_diffusion_1_1: T,
+    pub input_diffusion_1_2: T,
+    pub input_diffusion_2_1: T,
+    pub input_diffusion_2_2: T,
+
+    pub decay_diffusion_1_1: T,
+    pub decay_diffusion_1_2: T,
+    pub decay_diffusion_2_1: T,
+    pub decay_diffusion_2_2: T,
+
+    pub dumping_1: T,
+    pub dumping_2: T,
+
+    pub delay_1: T,
+    pub delay_2: T,
+    pub delay_3: T,
+    pub delay_4: T,
+
+    pub tank: T,
+    pub shimmer: T,
+
+    pub reverse_1: T,
+    pub reverse_2: T,
+
+    pub _t: PhantomData<V>,
+}
+
+impl<T, V> PlateBuffers<T, V>
+where
+    T: AsMut<[V]>,
+    V: num_traits::Num + num_traits::Signed + num_traits::FromPrimitive + Clone,
+{
+    pub fn build(&mut self) -> Plate<'_, V> {
+        self.build_with_lengths(PlateLengths::default())
+    }
+
+    /// Builds a plate running at the sample rate of `lengths`.
+    ///
+    /// Each buffer has to be longer than its scaled length,
+    /// and the decay diffusions 1 additionally by [`EXCURSION`].
+    pub fn build_with_lengths(&mut self, lengths: PlateLengths) -> Plate<'_, V> {
+        self.as_slices().into_plate(lengths)
+    }
+}
+
+impl<T, V> PlateBuffers<T, V>
+where
+    T: AsMut<[V]>,
+{
+    /// Borrows every buffer as a slice.
+    pub fn as_slices(&mut self) -> PlateBuffers<&mut [V], V> {
+        PlateBuffers {
+            predelay: self.predelay.as_mut(),
+            prefilter: self.prefilter.as_mut(),
+            input_diffusion_1_1: self.input_diffusion_1_1.as_mut(),
+            input_diffusion_1_2: self.input_diffusion_1_2.as_mut(),
+            input_diffusion_2_1: self.input_diffusion_2_1.as_mut(),
+            input_diffusion_2_2: self.input_diffusion_2_2.as_mut(),
+            decay_diffusion_1_1: self.decay_diffusion_1_1.as_mut(),
+            decay_diffusion_1_2: self.decay_diffusion_1_2.as_mut(),
+            decay_diffusion_2_1: self.decay_diffusion_2_1.as_mut(),
+            decay_diffusion_2_2: self.decay_diffusion_2_2.as_mut(),
+            dumping_1: self.dumping_1.as_mut(),
+            dumping_2: self.dumping_2.as_mut(),
+            delay_1: self.delay_1.as_mut(),
+            delay_2: self.delay_2.as_mut(),
+            delay_3: self.delay_3.as_mut(),
+            delay_4: self.delay_4.as_mut(),
+            tank: self.tank.as_mut(),
+            shimmer: self.shimmer.as_mut(),
+            reverse_1: self.reverse_1.as_mut(),
+            reverse_2: self.reverse_2.as_mut(),
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<'a, V> PlateBuffers<&'a mut [V], V> {
+    /// Splits `memory` into consecutive buffers of the given lengths.
+    ///
+    /// ## Panics
+    /// - If `memory` is shorter than the total of `lengths`.
+    pub fn split(memory: &'a mut [V], lengths: &PlateBuffers<usize, ()>) -> Self {
+        if memory.len() < lengths.total() {
+            panic!("memory is too short");
+        }
+        let mut lengths = lengths.to_array().into_iter();
+        let mut rest = memory;
+        let mut next = || {
+            let (buffer, tail) = core::mem::take(&mut rest).split_at_mut(lengths.next().unwrap());
+            rest = tail;
+            buffer
+        };
+        PlateBuffers {
+            predelay: next(),
+            prefilter: next(),
+            input_diffusion_1_1: next(),
+            input_diffusion_1_2: next(),
+            input_diffusion_2_1: next(),
+            input_diffusion_2_2: next(),
+            decay_diffusion_1_1: next(),
+            decay_diffusion_1_2: next(),
+            decay_diffusion_2_1: next(),
+            decay_diffusion_2_2: next(),
+            dumping_1: next(),
+            dumping_2: next(),
+            delay_1: next(),
+            delay_2: next(),
+            delay_3: next(),
+            delay_4: next(),
+            tank: next(),
+            shimmer: next(),
+            reverse_1: next(),
+            reverse_2: next(),
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<'a, V> PlateBuffers<&'a mut [V], V>
+where
+    V: num_traits::Num + num_traits::Signed + num_traits::FromPrimitive + Clone,
+{
+    /// Like [`PlateBuffers::build_with_lengths`],
+    /// but the plate keeps the buffers for as long as they are borrowed.
+    ///
+    /// An empty shimmer buffer leaves out the shimmer and empty reverse buffers the reverse mode.
+    pub fn into_plate(self, lengths: PlateLengths) -> Plate<'a, V> {
+        Plate {
+            lengths,
+            predelay: Delay::new(self.predelay),
+            predelay_length: 1,
+            prefilter: IIR::new(self.prefilter),
+            input_diffusion_1_1: APF::new(self.input_diffusion_1_1),
+            input_diffusion_1_2: APF::new(self.input_diffusion_1_2),
+            input_diffusion_2_1: APF::new(self.input_diffusion_2_1),
+            input_diffusion_2_2: APF::new(self.input_diffusion_2_2),
+            tank: self.tank,
+            shimmer: (!self.shimmer.is_empty()).then(|| PitchShifter::new(self.shimmer)),
+            shimmer_amount: V::zero(),
+            decay_diffusion_1_1: APF::new(self.decay_diffusion_1_1),
+            decay_diffusion_1_2: APF::new(self.decay_diffusion_1_2),
+            decay_diffusion_2_1: APF::new(self.decay_diffusion_2_1),
+            decay_diffusion_2_2: APF::new(self.decay_diffusion_2_2),
+            damping_1: IIR::new(self.dumping_1),
+            damping_2: IIR::new(self.dumping_2),
+            saturator_1: Saturator::new(V::zero(), V::zero(), V::zero()),
+            saturator_2: Saturator::new(V::zero(), V::zero(), V::zero()),
+            delay_1: Delay::new(self.delay_1),
+            delay_2: Delay::new(self.delay_2),
+            delay_3: Delay::new(self.delay_3),
+            delay_4: Delay::new(self.delay_4),
+            decay: V::zero(),
+            reversers: (!self.reverse_1.is_empty())
+                .then(|| [Reverser::new(self.reverse_1), Reverser::new(self.reverse_2)]),
+            reverse: false,
+        }
+    }
+}
+
+impl PlateBuffers<usize, ()> {
+    /// Lengths each buffer needs for a plate running at `lengths`,
+    /// with a pre delay of up to `max_predelay` samples,
+    /// reverse windows of up to `max_reverse_window` samples
+    /// and a shimmer window of `shimmer_window` samples.
+    /// A window of 0 leaves the reverse mode or the shimmer out of the plate.
+    ///
+    /// ```rust
+    /// # use oxide_plate_dsp::plate::*;
+    /// let required = PlateBuffers::required_lengths(&PlateLengths::default(), 0, REVERSE, SHIMMER);
+    /// assert_eq!(DECAY_DIFFUSION_1_1 + EXCURSION + 1, required.decay_diffusion_1_1);
+    ///
+    /// let required = PlateBuffers::required_lengths(&PlateLengths::default(), 0, 0, 0);
+    /// assert_eq!(0, required.reverse_1);
+    /// ```
+    pub const fn required_lengths(
+        lengths: &PlateLengths,
+        max_predelay: usize,
+        max_reverse_window: usize,
+        shimmer_window: usize,
+    ) -> Self {
+        PlateBuffers {
+            predelay: max_predelay + 1,
+            prefilter: 1,
+            input_diffusion_1_1: lengths.input_diffusion_1_1 + 1,
+            input_diffusion_1_2: lengths.input_diffusion_1_2 + 1,
+            input_diffusion_2_1: lengths.input_diffusion_2_1 + 1,
+            input_diffusion_2_2: lengths.input_diffusion_2_2 + 1,
+            decay_diffusion_1_1: lengths.decay_diffusion_1_1 + EXCURSION + 1,
+            decay_diffusion_1_2: lengths.decay_diffusion_1_2 + EXCURSION + 1,
+            decay_diffusion_2_1: lengths.decay_diffusion_2_1 + 1,
+            decay_diffusion_2_2: lengths.decay_diffusion_2_2 + 1,
+            dumping_1: 1,
+            dumping_2: 1,
+            delay_1: lengths.delay_1 + 1,
+            delay_2: lengths.delay_2 + 1,
+            delay_3: lengths.delay_3 + 1,
+            delay_4: lengths.delay_4 + 1,
+            tank: 2,
+            shimmer: if shimmer_window == 0 {
+                0
+            } else {
+                shimmer_window + 2
+            },
+            reverse_1: 2 * max_reverse_window,
+            reverse_2: 2 * max_reverse_window,
+            _t: PhantomData,
+        }
+    }
+
+    /// Sum of all lengths.
+    pub const fn total(&self) -> usize {
+        let lengths = self.to_array();
+        let mut total = 0;
+        let mut i = 0;
+        while i < lengths.len() {
+            total += lengths[i];
+            i += 1;
+        }
+        total
+    }
+
+    /// The lengths in the order of the fields.
+    const fn to_array(&self) -> [usize; 20] {
+        [
+            self.predelay,
+            self.prefilter,
+            self.input_diffusion_1_1,
+            self.input_diffusion_1_2,
+            self.input_diffusion_2_1,
+            self.input_diffusion_2_2,
+            self.decay_diffusion_1_1,
+            self.decay_diffusion_1_2,
+            self.decay_diffusion_2_1,
+            self.decay_diffusion_2_2,
+            self.dumping_1,
+            self.dumping_2,
+            self.delay_1,
+            self.delay_2,
+            self.delay_3,
+            self.delay_4,
+            self.tank,
+            self.shimmer,
+            self.reverse_1,
+            self.reverse_2,
+        ]
+    }
+}
+
+pub struct Plate<'a, T> {
+    lengths: PlateLengths,
+
+    predelay: Delay<'a, T>,
+    predelay_length: usize,
+    prefilter: IIR<'a, T, 1>,
+
+    input_diffusion_1_1: APF<'a, T>,
+    input_diffusion_1_2: APF<'a, T>,
+    input_diffusion_2_1: APF<'a, T>,
+    input_diffusion_2_2: APF<'a, T>,
+
+    tank: &'a mut [T],
+    /// Absent if the plate was built without a shimmer buffer.
+    shimmer: Option<PitchShifter<'a, T>>,
+    shimmer_amount: T,
+
+    decay_diffusion_1_1: APF<'a, T>,
+    decay_diffusion_1_2: APF<'a, T>,
+    decay_diffusion_2_1: APF<'a, T>,
+    decay_diffusion_2_2: APF<'a, T>,
+
+    damping_1: IIR<'a, T, 1>,
+    damping_2: IIR<'a, T, 1>,
+    saturator_1: Saturator<T>,
+    saturator_2: Saturator<T>,
+
+    delay_1: Delay<'a, T>,
+    delay_2: Delay<'a, T>,
+    delay_3: Delay<'a, T>,
+    delay_4: Delay<'a, T>,
+
+    decay: T,
+
+    /// Absent if the plate was built without reverse buffers.
+    reversers: Option<[Reverser<'a, T>; 2]>,
+    reverse: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlateParams<T> {
+    pub predelay: usize,
+
+    pub bandwidth: T,
+
+    pub input_diffusion_1: T,
+    pub input_diffusion_2: T,
+
+    pub decay_diffusion_1: T,
+    pub decay_diffusion_2: T,
+
+    pub decay_modulation: isize,
+
+    pub damping: T,
+
+    pub decay: T,
+
+    /// Gain into the saturation inside the tank, 0 to bypass it.
+    pub drive: T,
+    /// Asymmetry of the saturation, from 0 for symmetric to 1 for the positive half only.
+    pub character: T,
+
+    /// Share of the tank's feedback that is pitch shifted, from 0 to 1.
+    pub shimmer: T,
+    pub shimmer_interval: isize,
+
+    pub reverse: bool,
+    pub reverse_window: usize,
+}
+
+impl Default for PlateParams<f32> {
+    fn default() -> Self {
+        Self {
+            predelay: 1,
+            bandwidth: 0.9995,
+            input_diffusion_1: 0.750,
+            input_diffusion_2: 0.625,
+            decay_diffusion_1: 0.70,
+            decay_diffusion_2: 0.50,
+            damping: 0.0005,
+            decay: 0.50,
+            drive: 0.0,
+            character: 0.0,
+            decay_modulation: 0,
+            shimmer: 0.0,
+            shimmer_interval: 12,
+            reverse: false,
+            reverse_window: 16384,
+        }
+    }
+}
+
+impl Default for PlateParams<f64> {
+    fn default() -> Self {
+        Self {
+            predelay: 1,
+            bandwidth: 0.9995,
+            input_diffusion_1: 0.750,
+            input_diffusion_2: 0.625,
+            decay_diffusion_1: 0.70,
+            decay_diffusion_2: 0.50,
+            damping: 0.0005,
+            decay: 0.50,
+            drive: 0.0,
+            character: 0.0,
+            decay_modulation: 0,
+            shimmer: 0.0,
+            shimmer_interval: 12,
+            reverse: false,
+            reverse_window: 16384,
+        }
+    }
+}
+
+impl<T> PlateParams<T> {
+    /// Converts the coefficients to another sample type, such as `f32` to `f64`.
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> PlateParams<U> {
+        PlateParams {
+            predelay: self.predelay,
+            bandwidth: f(self.bandwidth),
+            input_diffusion_1: f(self.input_diffusion_1),
+            input_diffusion_2: f(self.input_diffusion_2),
+            decay_diffusion_1: f(self.decay_diffusion_1),
+            decay_diffusion_2: f(self.decay_diffusion_2),
+            decay_modulation: self.decay_modulation,
+            damping: f(self.damping),
+            decay: f(self.decay),
+            drive: f(self.drive),
+            character: f(self.character),
+            shimmer: f(self.shimmer),
+            shimmer_interval: self.shimmer_interval,
+            reverse: self.reverse,
+            reverse_window: self.reverse_window,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> PlateParams<T>
+where
+    T: num_traits::Float,
+{
+    /// Converts the coefficients of the one-pole filters for a plate running `factor` times
+    /// as fast, so that their cutoff stays the same.
+    pub fn scale_filters(self, factor: T) -> Self {
+        // The `factor`-th root of a pole decays as far in `factor` steps as the pole in one.
+        let exponent = T::one() / factor;
+        Self {
+            bandwidth: T::one() - (T::one() - self.bandwidth).powf(exponent),
+            damping: self.damping.powf(exponent),
+            ..self
+        }
+    }
+
+    /// Converts parameters for a plate at [`SAMPLE_RATE`] to one running at `sample_rate`,
+    /// scaling the pre delay, the reverse window and the filters so that both sound the same.
+    pub fn at_sample_rate(self, sample_rate: f64) -> Self {
+        let factor = sample_rate / SAMPLE_RATE;
+        let samples = |length: usize| ((length as f64 * factor).round() as usize).max(1);
+        Self {
+            predelay: samples(self.predelay),
+            reverse_window: samples(self.reverse_window).min(REVERSE),
+            ..self.scale_filters(T::from(factor).unwrap())
+        }
+    }
+}
+
+pub const INPUT_DIFFUSION_1_1: usize = 142;
+pub const INPUT_DIFFUSION_1_2: usize = 107;
+pub const INPUT_DIFFUSION_2_1: usize = 379;
+pub const INPUT_DIFFUSION_2_2: usize = 277;
+
+pub const DECAY_DIFFUSION_1_1: usize = 672;
+pub const DECAY_DIFFUSION_1_2: usize = 908;
+pub const DECAY_DIFFUSION_2_1: usize = 1800;
+pub const DECAY_DIFFUSION_2_2: usize = 2656;
+
+pub const EXCURSION: usize = 16;
+
+pub const SHIMMER: usize = 2048;
+
+pub const REVERSE: usize = 65536;
+
+pub const DELAY_1: usize = 4453;
+pub const DELAY_2: usize = 3720;
+pub const DELAY_3: usize = 4217;
+pub const DELAY_4: usize = 3163;
+
+/// Output taps in the order [`Plate::process_2ch`] reads them.
+pub const TAPS: [usize; 14] = [
+    266, 2974, 1913, 1996, 1990, 187, 1066, 353, 3627, 1228, 2673, 2111, 335, 121,
+];
+
+/// Longest run of samples [`Plate::process_block`] processes at once.
+const BLOCK: usize = 32;
+
+/// Time over which the saturators follow the level of the tank, in seconds.
+const SATURATOR_LEVEL: f64 = 0.01;
+
+/// Sample rate the delay lengths above are specified at.
+pub const SAMPLE_RATE: f64 = 29761.0;
+
+/// Delay lengths of a plate, scaled to the sample rate it runs at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlateLengths {
+    pub sample_rate: f64,
+
+    pub input_diffusion_1_1: usize,
+    pub input_diffusion_1_2: usize,
+    pub input_diffusion_2_1: usize,
+    pub input_diffusion_2_2: usize,
+
+    pub decay_diffusion_1_1: usize,
+    pub decay_diffusion_1_2: usize,
+    pub decay_diffusion_2_1: usize,
+    pub decay_diffusion_2_2: usize,
+
+    pub delay_1: usize,
+    pub delay_2: usize,
+    pub delay_3: usize,
+    pub delay_4: usize,
+
+    pub taps: [usize; 14],
+}
+
+impl PlateLengths {
+    pub const fn new(sample_rate: f64) -> Self {
+        let mut taps = TAPS;
+        let mut i = 0;
+        while i < taps.len() {
+            taps[i] = scale(taps[i], sample_rate);
+            i += 1;
+        }
+        Self {
+            sample_rate,
+            input_diffusion_1_1: scale(INPUT_DIFFUSION_1_1, sample_rate),
+            input_diffusion_1_2: scale(INPUT_DIFFUSION_1_2, sample_rate),
+            input_diffusion_2_1: scale(INPUT_DIFFUSION_2_1, sample_rate),
+            input_diffusion_2_2: scale(INPUT_DIFFUSION_2_2, sample_rate),
+            decay_diffusion_1_1: scale(DECAY_DIFFUSION_1_1, sample_rate),
+            decay_diffusion_1_2: scale(DECAY_DIFFUSION_1_2, sample_rate),
+            decay_diffusion_2_1: scale(DECAY_DIFFUSION_2_1, sample_rate),
+            decay_diffusion_2_2: scale(DECAY_DIFFUSION_2_2, sample_rate),
+            delay_1: scale(DELAY_1, sample_rate),
+            delay_2: scale(DELAY_2, sample_rate),
+            delay_3: scale(DELAY_3, sample_rate),
+            delay_4: scale(DELAY_4, sample_rate),
+            taps,
+        }
+    }
+
+    /// Scales a length given at [`SAMPLE_RATE`] to this sample rate.
+    pub const fn scale(&self, length: usize) -> usize {
+        scale(length, self.sample_rate)
+    }
+}
+
+impl Default for PlateLengths {
+    fn default() -> Self {
+        Self::new(SAMPLE_RATE)
+    }
+}
+
+const fn scale(length: usize, sample_rate: f64) -> usize {
+    let scaled = (length as f64 * sample_rate / SAMPLE_RATE + 0.5) as usize;
+    if scaled > 1 {
+        scaled
+    } else {
+        1
+    }
+}
+
+impl<'a, T> Plate<'a, T>
+where
+    T: num_traits::Num + num_traits::One + num_traits::Signed + num_traits::FromPrimitive + Clone,
+{
+    pub fn set_params(&mut self, params: PlateParams<T>) {
+        self.predelay_length = params.predelay;
+        self.prefilter
+            .set_params([T::one() - params.bandwidth.clone()], params.bandwidth);
+
+        self.input_diffusion_1_1.set_params(
+            params.input_diffusion_1.clone(),
+            params.input_diffusion_1.clone(),
+            self.lengths.input_diffusion_1_1.try_into().unwrap(),
+        );
+        self.input_diffusion_1_2.set_params(
+            params.input_diffusion_1.clone(),
+            params.input_diffusion_1.clone(),
+            self.lengths.input_diffusion_1_2.try_into().unwrap(),
+        );
+        self.input_diffusion_2_1.set_params(
+            params.input_diffusion_2.clone(),
+            params.input_diffusion_2.clone(),
+            self.lengths.input_diffusion_2_1.try_into().unwrap(),
+        );
+        self.input_diffusion_2_2.set_params(
+            params.input_diffusion_2.clone(),
+            params.input_diffusion_2.clone(),
+            self.lengths.input_diffusion_2_2.try_into().unwrap(),
+        );
+
+        self.decay_diffusion_1_1.set_params(
+            -params.decay_diffusion_1.clone(),
+            -params.decay_diffusion_1.clone(),
+            ((self.lengths.decay_diffusion_1_1 as isize + params.decay_modulation) as usize)
+                .try_into()
+                .unwrap(),
+        );
+        self.decay_diffusion_1_2.set_params(
+            -params.decay_diffusion_1.clone(),
+            -params.decay_diffusion_1.clone(),
+            ((self.lengths.decay_diffusion_1_2 as isize + params.decay_modulation) as usize)
+                .try_into()
+                .unwrap(),
+        );
+        self.decay_diffusion_2_1.set_params(
+            params.decay_diffusion_2.clone(),
+            params.decay_diffusion_2.clone(),
+            self.lengths.decay_diffusion_2_1.try_into().unwrap(),
+        );
+        self.decay_diffusion_2_2.set_params(
+            params.decay_diffusion_2.clone(),
+            params.decay_diffusion_2.clone(),
+            self.lengths.decay_diffusion_2_2.try_into().unwrap(),
+        );
+
+        self.damping_1
+            .set_params([params.damping.clone()], T::one() - params.damping.clone());
+        self.damping_2
+            .set_params([params.damping.clone()], T::one() - params.damping.clone());
+
+        self.decay = params.decay;
+        let smoothing =
+            T::from_f64(1.0 - 1.0 / (SATURATOR_LEVEL * self.lengths.sample_rate)).unwrap();
+        for saturator in [&mut self.saturator_1, &mut self.saturator_2] {
+            saturator.set_params(
+                params.drive.clone(),
+                params.character.clone(),
+                smoothing.clone(),
+            );
+        }
+
+        if let Some(shimmer) = &mut self.shimmer {
+            shimmer.set_ratio(interval_to_ratio(params.shimmer_interval));
+            self.shimmer_amount = params.shimmer;
+        }
+
+        if let Some(reversers) = &mut self.reversers {
+            self.reverse = params.reverse;
+            for reverser in reversers {
+                reverser.set_window(params.reverse_window.try_into().unwrap());
+            }
+        }
+    }
+
+    /// Returns the latency added by the reverse mode, in samples.
+    pub fn latency(&self) -> usize {
+        match &self.reversers {
+            Some([reverser, _]) if self.reverse => reverser.window(),
+            _ => 0,
+        }
+    }
+
+    pub fn process(&mut self, x: &[T]) {
+        self.tick(mean(x));
+    }
+
+    /// Runs one mono sample through the input diffusers and the tank.
+    fn tick(&mut self, x: T) {
+        let mut acc = x;
+
+        self.predelay.write(acc.clone());
+        acc = self
+            .predelay
+            .read(self.predelay_length.try_into().unwrap())
+            .clone();
+
+        acc = self.prefilter.tick(acc.clone());
+
+        acc = self.input_diffusion_1_1.tick(acc.clone());
+        acc = self.input_diffusion_1_2.tick(acc.clone());
+        acc = self.input_diffusion_2_1.tick(acc.clone());
+        acc = self.input_diffusion_2_2.tick(acc.clone());
+
+        // The shimmer replaces its share of the feedback instead of adding to it,
+        // so that the tank keeps losing energy as long as the decay is below 1.
+        let shimmer = self.shimmer(self.tank[0].clone(), self.tank[1].clone());
+        let keep = T::one() - self.shimmer_amount.clone();
+
+        let mut tank1 = acc.clone() + (keep.clone() * self.tank[0].clone() + shimmer.clone());
+        tank1 = self.decay_diffusion_1_1.tick(tank1.clone());
+        self.delay_1.write(tank1.clone());
+        tank1 = self
+            .delay_1
+            .read(self.lengths.delay_1.try_into().unwrap())
+            .clone();
+        tank1 = self.saturator_1.tick(tank1);
+        tank1 = self.damping_1.tick(tank1.clone());
+        tank1 = self.decay.clone() * tank1.clone();
+        tank1 = self.decay_diffusion_2_1.tick(tank1.clone());
+        self.delay_2.write(tank1.clone());
+        tank1 = self
+            .delay_2
+            .read(self.lengths.delay_2.try_into().unwrap())
+            .clone();
+        tank1 = self.decay.clone() * tank1;
+
+        let mut tank2 = acc.clone() + (keep * self.tank[1].clone() + shimmer);
+        tank2 = self.decay_diffusion_1_2.tick(tank2.clone());
+        self.delay_3.write(tank2.clone());
+        tank2 = self
+            .delay_3
+            .read(self.lengths.delay_3.try_into().unwrap())
+            .clone();
+        tank2 = self.saturator_2.tick(tank2);
+        tank2 = self.damping_2.tick(tank2.clone());
+        tank2 = self.decay.clone() * tank2.clone();
+        tank2 = self.decay_diffusion_2_2.tick(tank2.clone());
+        self.delay_4.write(tank2.clone());
+        tank2 = self
+            .delay_4
+            .read(self.lengths.delay_4.try_into().unwrap())
+            .clone();
+        tank2 = self.decay.clone() * tank2;
+
+        self.tank[0] = tank2;
+        self.tank[1] = tank1;
+    }
+
+    /// Pitch shifts the mean of the feedback into both halves, scaled by the shimmer amount.
+    fn shimmer(&mut self, feedback_1: T, feedback_2: T) -> T {
+        let Some(shimmer) = &mut self.shimmer else {
+            return T::zero();
+        };
+        let half = T::from_f64(0.5).unwrap();
+        self.shimmer_amount.clone() * shimmer.tick(half.clone() * feedback_1 + half * feedback_2)
+    }
+
+    /// Plays the output back reversed in the reverse mode.
+    fn reverse(&mut self, left: T, right: T) -> [T; 2] {
+        let Some([reverse_1, reverse_2]) = &mut self.reversers else {
+            return [left, right];
+        };
+        // Keep recording while disabled so that switching on plays back the recent tail.
+        let left_reversed = reverse_1.tick(left.clone());
+        let right_reversed = reverse_2.tick(right.clone());
+        if self.reverse {
+            [left_reversed, right_reversed]
+        } else {
+            [left, right]
+        }
+    }
+}
+
+impl<'a, T> Plate<'a, T>
+where
+    T: num_traits::Num
+        + num_traits::NumAssign
+        + num_traits::Signed
+        + num_traits::FromPrimitive
+        + Clone,
+{
+    pub fn process_2ch(&mut self, x: &[T]) -> [T; 2] {
+        self.process(x);
+        let taps = self.taps();
+        self.output(&taps)
+    }
+
+    /// Processes whole buffers, writing the left and right output of each frame
+    /// into the first and second output channel, exactly as [`Plate::process_2ch`] would.
+    ///
+    /// ## Panics
+    /// - If `inputs` is empty.
+    /// - If there are more than two outputs.
+    /// - If the channels differ in length.
+    pub fn process_block(&mut self, inputs: &[&[T]], outputs: &mut [&mut [T]]) {
+        if outputs.len() > 2 {
+            panic!("more than two outputs");
+        }
+        let len = inputs[0].len();
+        if inputs.iter().any(|x| x.len() != len) || outputs.iter().any(|y| y.len() != len) {
+            panic!("channels differ in length");
+        }
+
+        let scale = T::from_f64(1.0 / inputs.len() as f64).unwrap();
+        let block = self.block_len();
+        if block == 0 {
+            // Delays too short to run ahead of, only at sample rates far below any in use.
+            let taps = self.taps();
+            for i in 0..len {
+                let x = inputs
+                    .iter()
+                    .map(|x| x[i].clone() * scale.clone())
+                    .reduce(|acc, x| acc + x)
+                    .unwrap();
+                self.tick(x);
+                let y = self.output(&taps);
+                for (output, y) in outputs.iter_mut().zip(y) {
+                    output[i] = y;
+                }
+            }
+            return;
+        }
+
+        let mut x = zeros();
+        let mut y = [zeros(), zeros()];
+        let mut start = 0;
+        while start < len {
+            let end = len.min(start + block);
+            let x = &mut x[..end - start];
+            for (x, input) in x.iter_mut().zip(&inputs[0][start..end]) {
+                *x = input.clone() * scale.clone();
+            }
+            for input in &inputs[1..] {
+                for (x, input) in x.iter_mut().zip(&input[start..end]) {
+                    *x = x.clone() + input.clone() * scale.clone();
+                }
+            }
+
+            let [left, right] = &mut y;
+            self.tick_block(x, &mut left[..end - start], &mut right[..end - start]);
+            for (output, y) in outputs.iter_mut().zip(&y) {
+                output[start..end].clone_from_slice(&y[..end - start]);
+            }
+            start = end;
+        }
+    }
+
+    /// Returns the longest block [`Plate::tick_block`] can process at once, at most [`BLOCK`].
+    ///
+    /// A block must not be longer than any all-pass delay, so that no diffuser reads
+    /// what the same block writes, and must be shorter than the tank delays,
+    /// whose outputs are read ahead for the whole block.
+    /// The output taps are read once the block is written,
+    /// so their lines also have to still hold the first sample's taps by then.
+    fn block_len(&self) -> usize {
+        let lengths = &self.lengths;
+        let diffusers = [
+            &self.input_diffusion_1_1,
+            &self.input_diffusion_1_2,
+            &self.input_diffusion_2_1,
+            &self.input_diffusion_2_2,
+            &self.decay_diffusion_1_1,
+            &self.decay_diffusion_1_2,
+            &self.decay_diffusion_2_1,
+            &self.decay_diffusion_2_2,
+        ]
+        .map(|apf| apf.delay().get());
+        let delays = [
+            lengths.delay_1,
+            lengths.delay_2,
+            lengths.delay_3,
+            lengths.delay_4,
+        ]
+        .map(|length| length - 1);
+        let tapped = [
+            lengths.delay_3,
+            lengths.delay_3,
+            lengths.decay_diffusion_2_2,
+            lengths.delay_4,
+            lengths.delay_1,
+            lengths.decay_diffusion_2_1,
+            lengths.delay_2,
+            lengths.delay_1,
+            lengths.delay_1,
+            lengths.decay_diffusion_2_1,
+            lengths.delay_2,
+            lengths.delay_3,
+            lengths.decay_diffusion_2_2,
+            lengths.delay_4,
+        ];
+        // Each line holds at least `length + 1` samples.
+        let taps = tapped
+            .iter()
+            .zip(lengths.taps)
+            .map(|(length, tap)| (length + 2).saturating_sub(tap));
+
+        diffusers
+            .into_iter()
+            .chain(delays)
+            .chain(taps)
+            .fold(BLOCK, usize::min)
+    }
+
+    /// Runs a block of mono samples through the plate and writes its left and right output,
+    /// exactly as [`Plate::tick`] followed by [`Plate::output`] would sample by sample.
+    ///
+    /// Delay lines and diffusers are read and written in contiguous runs;
+    /// only the recursive filters, the saturator, the shimmer and the reversers
+    /// run sample by sample.
+    /// The block must not be longer than [`Plate::block_len`].
+    fn tick_block(&mut self, acc: &mut [T], left: &mut [T], right: &mut [T]) {
+        let n = acc.len();
+        let mut scratch = zeros();
+        let z = &mut scratch[..n];
+        let mut delayed = zeros();
+        let delayed = &mut delayed[..n];
+
+        // Samples delayed past the start of the block come from the block itself.
+        let predelay = self.predelay_length - 1;
+        let old = predelay.min(n);
+        if let Some(predelay) = NonZeroUsize::new(predelay) {
+            self.predelay.read_block(predelay, &mut delayed[..old]);
+        }
+        delayed[old..].clone_from_slice(&acc[..n - old]);
+        self.predelay.write_block(acc);
+        for (acc, x) in acc.iter_mut().zip(delayed.iter()) {
+            *acc = self.prefilter.tick(x.clone());
+        }
+
+        self.input_diffusion_1_1.process_block(acc, z);
+        self.input_diffusion_1_2.process_block(acc, z);
+        self.input_diffusion_2_1.process_block(acc, z);
+        self.input_diffusion_2_2.process_block(acc, z);
+
+        let delay = |length: usize| NonZeroUsize::new(length - 1).unwrap();
+        let mut output_2 = zeros();
+        let output_2 = &mut output_2[..n];
+        self.delay_2
+            .read_block(delay(self.lengths.delay_2), output_2);
+        let mut output_4 = zeros();
+        let output_4 = &mut output_4[..n];
+        self.delay_4
+            .read_block(delay(self.lengths.delay_4), output_4);
+
+        // Each half is fed the other half's output of the sample before.
+        let decay = self.decay.clone();
+        let mut feedback_1 = zeros();
+        let feedback_1 = &mut feedback_1[..n];
+        let mut feedback_2 = zeros();
+        let feedback_2 = &mut feedback_2[..n];
+        feedback_1[0] = self.tank[0].clone();
+        feedback_2[0] = self.tank[1].clone();
+        for ((feedback, output_4), (feedback_2, output_2)) in feedback_1[1..]
+            .iter_mut()
+            .zip(output_4.iter())
+            .zip(feedback_2[1..].iter_mut().zip(output_2.iter()))
+        {
+            *feedback = decay.clone() * output_4.clone();
+            *feedback_2 = decay.clone() * output_2.clone();
+        }
+        self.tank[0] = decay.clone() * output_4[n - 1].clone();
+        self.tank[1] = decay.clone() * output_2[n - 1].clone();
+
+        let keep = T::one() - self.shimmer_amount.clone();
+        let mut tank1 = zeros();
+        let tank1 = &mut tank1[..n];
+        let mut tank2 = zeros();
+        let tank2 = &mut tank2[..n];
+        for (((tank1, tank2), acc), (feedback_1, feedback_2)) in tank1
+            .iter_mut()
+            .zip(tank2.iter_mut())
+            .zip(acc.iter())
+            .zip(feedback_1.iter().zip(feedback_2.iter()))
+        {
+            let shimmer = self.shimmer(feedback_1.clone(), feedback_2.clone());
+            *tank1 = acc.clone() + (keep.clone() * feedback_1.clone() + shimmer.clone());
+            *tank2 = acc.clone() + (keep.clone() * feedback_2.clone() + shimmer);
+        }
+
+        self.decay_diffusion_1_1.process_block(tank1, z);
+        self.delay_1
+            .read_block(delay(self.lengths.delay_1), delayed);
+        self.delay_1.write_block(tank1);
+        for (tank1, x) in tank1.iter_mut().zip(delayed.iter()) {
+            let x = self.saturator_1.tick(x.clone());
+            let x = self.damping_1.tick(x);
+            *tank1 = decay.clone() * x;
+        }
+        self.decay_diffusion_2_1.process_block(tank1, z);
+        self.delay_2.write_block(tank1);
+
+        self.decay_diffusion_1_2.process_block(tank2, z);
+        self.delay_3
+            .read_block(delay(self.lengths.delay_3), delayed);
+        self.delay_3.write_block(tank2);
+        for (tank2, x) in tank2.iter_mut().zip(delayed.iter()) {
+            let x = self.saturator_2.tick(x.clone());
+            let x = self.damping_2.tick(x);
+            *tank2 = decay.clone() * x;
+        }
+        self.decay_diffusion_2_2.process_block(tank2, z);
+        self.delay_4.write_block(tank2);
+
+        // Read back from the end of the block, the tap of the first sample lies `n - 1` further.
+        let taps = self.lengths.taps;
+        let tap = |i: usize| NonZeroUsize::new(n - 1 + taps[i]).unwrap();
+        let add = |ys: &mut [T], xs: &[T]| {
+            for (y, x) in ys.iter_mut().zip(xs) {
+                *y += x.clone();
+            }
+        };
+        let sub = |ys: &mut [T], xs: &[T]| {
+            for (y, x) in ys.iter_mut().zip(xs) {
+                *y -= x.clone();
+            }
+        };
+
+        self.delay_3.read_block(tap(0), left);
+        self.delay_3.read_block(tap(1), z);
+        add(left, z);
+        self.decay_diffusion_2_2.sample_block(tap(2), z);
+        sub(left, z);
+        self.delay_4.read_block(tap(3), z);
+        add(left, z);
+        self.delay_1.read_block(tap(4), z);
+        sub(left, z);
+        self.decay_diffusion_2_1.sample_block(tap(5), z);
+        sub(left, z);
+        self.delay_2.read_block(tap(6), z);
+        sub(left, z);
+
+        self.delay_1.read_block(tap(7), right);
+        self.delay_1.read_block(tap(8), z);
+        add(right, z);
+        self.decay_diffusion_2_1.sample_block(tap(9), z);
+        sub(right, z);
+        self.delay_2.read_block(tap(10), z);
+        add(right, z);
+        self.delay_3.read_block(tap(11), z);
+        sub(right, z);
+        self.decay_diffusion_2_2.sample_block(tap(12), z);
+        sub(right, z);
+        self.delay_4.read_block(tap(13), z);
+        sub(right, z);
+
+        for (left, right) in left.iter_mut().zip(right.iter_mut()) {
+            [*left, *right] = self.reverse(left.clone(), right.clone());
+        }
+    }
+
+    fn taps(&self) -> [NonZeroUsize; 14] {
+        self.lengths.taps.map(|tap| NonZeroUsize::new(tap).unwrap())
+    }
+
+    /// Reads the output taps of the tank after a tick.
+    fn output(&mut self, taps: &[NonZeroUsize; 14]) -> [T; 2] {
+        let mut left_acc = self.delay_3.read(taps[0]).clone();
+        left_acc += self.delay_3.read(taps[1]).clone();
+        left_acc -= self.decay_diffusion_2_2.sample_buffer(taps[2]).clone();
+        left_acc += self.delay_4.read(taps[3]).clone();
+        left_acc -= self.delay_1.read(taps[4]).clone();
+        left_acc -= self.decay_diffusion_2_1.sample_buffer(taps[5]).clone();
+        left_acc -= self.delay_2.read(taps[6]).clone();
+
+        let mut right_acc = self.delay_1.read(taps[7]).clone();
+        right_acc += self.delay_1.read(taps[8]).clone();
+        right_acc -= self.decay_diffusion_2_1.sample_buffer(taps[9]).clone();
+        right_acc += self.delay_2.read(taps[10]).clone();
+        right_acc -= self.delay_3.read(taps[11]).clone();
+        right_acc -= self.decay_diffusion_2_2.sample_buffer(taps[12]).clone();
+        right_acc -= self.delay_4.read(taps[13]).clone();
+
+        self.reverse(left_acc, right_acc)
+    }
+}
+
+impl<'a, T> Plate<'a, T>
+where
+    T: Default + num_traits::One + Clone,
+{
+    /// Silences the plate by clearing every buffer.
+    pub fn clear(&mut self) {
+        self.predelay.clear();
+        self.prefilter.clear();
+        self.input_diffusion_1_1.clear();
+        self.input_diffusion_1_2.clear();
+        self.input_diffusion_2_1.clear();
+        self.input_diffusion_2_2.clear();
+        self.tank.fill(T::default());
+        if let Some(shimmer) = &mut self.shimmer {
+            shimmer.clear();
+        }
+        self.decay_diffusion_1_1.clear();
+        self.decay_diffusion_1_2.clear();
+        self.decay_diffusion_2_1.clear();
+        self.decay_diffusion_2_2.clear();
+        self.damping_1.clear();
+        self.damping_2.clear();
+        self.saturator_1.clear();
+        self.saturator_2.clear();
+        self.delay_1.clear();
+        self.delay_2.clear();
+        self.delay_3.clear();
+        self.delay_4.clear();
+        for reverser in self.reversers.iter_mut().flatten() {
+            reverser.clear();
+        }
+    }
+
+    /// Switches to the delay lengths of another sample rate and clears the plate.
+    ///
+    /// The buffers have to be long enough for `lengths`, such as buffers for
+    /// a higher sample rate. The all-pass filters pick up the new lengths
+    /// with the next [`Plate::set_params`].
+    pub fn set_lengths(&mut self, lengths: PlateLengths) {
+        self.lengths = lengths;
+        self.clear();
+    }
+}
+
+impl<'a, T> Plate<'a, T>
+where
+    T: num_traits::float::FloatCore
+        + num_traits::NumAssign
+        + num_traits::Signed
+        + num_traits::FromPrimitive
+        + Default,
+{
+    /// Like [`Plate::process_2ch`], but keeps NaN and infinity out of the tank.
+    ///
+    /// Frames with a non-finite sample are processed as silence,
+    /// and if the tank still ends up non-finite the plate is [cleared](Plate::clear)
+    /// and silence is returned.
+    pub fn process_2ch_checked(&mut self, x: &[T]) -> [T; 2] {
+        let y = if x.iter().all(|x| x.is_finite()) {
+            self.process_2ch(x)
+        } else {
+            self.process_2ch(&[T::zero()])
+        };
+        if y.iter().chain(self.tank.iter()).all(|y| y.is_finite()) {
+            y
+        } else {
+            self.clear();
+            [T::zero(), T::zero()]
+        }
+    }
+}
+
+/// How long the output has to stay below the threshold before the tail counts as decayed,
+/// which is the round trip of one tank half at [`SAMPLE_RATE`].
+pub const IMPULSE_RESPONSE_HOLD: usize = DELAY_1 + DELAY_2;
+
+impl<'a, T> Plate<'a, T>
+where
+    T: num_traits::Num
+        + num_traits::NumAssign
+        + num_traits::Signed
+        + num_traits::FromPrimitive
+        + PartialOrd
+        + Clone,
+{
+    /// Feeds a unit impulse and yields the stereo response
+    /// until it stays below `threshold` for [`IMPULSE_RESPONSE_HOLD`] scaled samples,
+    /// or `max_length` samples have been rendered.
+    ///
+    /// The plate should be [cleared](Plate::clear) beforehand to render from silence.
+    pub fn impulse_response(&mut self, threshold: T, max_length: usize) -> ImpulseTail<'_, 'a, T> {
+        ImpulseTail {
+            hold: self.lengths.scale(IMPULSE_RESPONSE_HOLD),
+            plate: self,
+            threshold,
+            remaining: max_length,
+            quiet: 0,
+            impulse: true,
+        }
+    }
+
+    /// Lets the plate ring out without input, like [`Plate::impulse_response`]
+    /// but from whatever it holds instead of a unit impulse.
+    pub fn tail(&mut self, threshold: T, max_length: usize) -> ImpulseTail<'_, 'a, T> {
+        ImpulseTail {
+            impulse: false,
+            ..self.impulse_response(threshold, max_length)
+        }
+    }
+}
+
+/// Iterator over the response of a [`Plate`] to a unit impulse, or over its [tail](Plate::tail).
+pub struct ImpulseTail<'p, 'a, T> {
+    plate: &'p mut Plate<'a, T>,
+    threshold: T,
+    remaining: usize,
+    hold: usize,
+    quiet: usize,
+    impulse: bool,
+}
+
+impl<'p, 'a, T> Iterator for ImpulseTail<'p, 'a, T>
+where
+    T: num_traits::Num
+        + num_traits::NumAssign
+        + num_traits::Signed
+        + num_traits::FromPrimitive
+        + PartialOrd
+        + Clone,
+{
+    type Item = [T; 2];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 || self.quiet >= self.hold {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let x = if self.impulse { T::one() } else { T::zero() };
+        self.impulse = false;
+        let y = self.plate.process_2ch(&[x]);
+
+        if y.iter().all(|y| y.abs() < self.threshold) {
+            self.quiet += 1;
+        } else {
+            self.quiet = 0;
+        }
+        Some(y)
+    }
+}
+
+/// Returns a block of zeros for [`Plate::tick_block`].
+fn zeros<T: num_traits::Zero>() -> [T; BLOCK] {
+    core::array::from_fn(|_| T::zero())
+}
+
+/// Scales before summing, so that fixed-point samples can't overflow
+/// and don't have to represent the channel count.
+fn mean<T>(xs: &[T]) -> T
+where
+    T: num_traits::NumOps + num_traits::FromPrimitive + Clone,
+{
+    let scale = T::from_f64(1.0 / xs.len() as f64).unwrap();
+    xs.iter()
+        .map(|x| x.clone() * scale.clone())
+        .reduce(|acc, x| acc + x)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impulse_response() {
+        let mut plate = Plate::<f64>::new(SAMPLE_RATE, 4095);
+        plate.set_params(PlateParams::default());
+
+        let tail: Vec<_> = plate.impulse_response(1e-6, 1 << 20).collect();
+        assert!(tail.len() < 1 << 20);
+        assert!(tail.iter().flatten().any(|y| y.abs() >= 1e-6));
+        assert!(tail[tail.len() - IMPULSE_RESPONSE_HOLD..]
+            .iter()
+            .flatten()
+            .all(|y| y.abs() < 1e-6));
+
+        plate.clear();
+        let again: Vec<_> = plate.impulse_response(1e-6, 1 << 20).collect();
+        assert_eq!(tail, again);
+
+        // Feeding the impulse by hand and letting the plate ring out is the same.
+        plate.clear();
+        let first = plate.process_2ch(&[1.0]);
+        let rest: Vec<_> = plate.tail(1e-6, (1 << 20) - 1).collect();
+        assert_eq!(tail[0], first);
+        assert_eq!(tail[1..], rest);
+    }
+
+    #[test]
+    fn non_finite() {
+        let mut arena = PlateArena::new(&PlateLengths::default(), 4095);
+        let mut plate: Plate<'_, f64> = arena.build_with_lengths(PlateLengths::default());
+        plate.set_params(PlateParams::default());
+        let reference: Vec<_> = plate.impulse_response(1e-6, 1 << 20).collect();
+
+        plate.clear();
+        for x in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            for _ in 0..100 {
+                let y = plate.process_2ch_checked(&[x, 1.0]);
+                assert!(y.iter().all(|y| y.is_finite()));
+            }
+        }
+        // Silence flushes the predelay, after which the plate behaves as before.
+        for _ in 0..10000 {
+            plate.process_2ch_checked(&[0.0]);
+        }
+        plate.clear();
+        let again: Vec<_> = plate.impulse_response(1e-6, 1 << 20).collect();
+        assert_eq!(reference, again);
+
+        // A tank poisoned from within is reset once the value surfaces.
+        plate.process_2ch_checked(&[1.0]);
+        plate.tank[0] = f64::NAN;
+        let mut y = [1.0; 2];
+        for _ in 0..20000 {
+            y = plate.process_2ch_checked(&[0.0]);
+            assert!(y.iter().all(|y| y.is_finite()));
+        }
+        assert_eq!([0.0, 0.0], y);
+    }
+
+    #[test]
+    fn shimmer_bounded() {
+        for shimmer in [0.5, 1.0] {
+            let mut plate = Plate::<f64>::new(SAMPLE_RATE, 4095);
+            plate.set_params(PlateParams {
+                bandwidth: 0.9999,
+                damping: 0.0001,
+                decay: 0.9999,
+                shimmer,
+                ..PlateParams::default()
+            });
+            // One second of noise, then four of feedback.
+            for t in 0..5 * SAMPLE_RATE as usize {
+                let x = if t < SAMPLE_RATE as usize {
+                    ((t * 7919) % 101) as f64 / 50.0 - 1.0
+                } else {
+                    0.0
+                };
+                let y = plate.process_2ch(&[x]);
+                assert!(y.iter().all(|y| y.abs() < 10.0));
+            }
+        }
+    }
+
+    #[test]
+    fn block() {
+        let params = PlateParams {
+            shimmer: 0.2,
+            ..PlateParams::default()
+        };
+        let left: Vec<f64> = (0..5000)
+            .map(|t| ((t * 7919) % 101) as f64 / 50.0 - 1.0)
+            .collect();
+        let right: Vec<f64> = left.iter().rev().copied().collect();
+
+        // Pre delays shorter and longer than a block, with and without the saturator.
+        for (reverse, predelay, drive) in [
+            (false, 1, 0.0),
+            (true, 1, 0.0),
+            (false, 10, 2.0),
+            (true, 100, 0.0),
+        ] {
+            let params = PlateParams {
+                reverse,
+                reverse_window: 1000,
+                predelay,
+                drive,
+                decay_modulation: 8,
+                ..params.clone()
+            };
+            let mut per_sample = Plate::<f64>::new(SAMPLE_RATE, 4095);
+            per_sample.set_params(params.clone());
+            let mut block = Plate::<f64>::new(SAMPLE_RATE, 4095);
+            block.set_params(params);
+
+            for inputs in [vec![&left[..]], vec![&left[..], &right[..]]] {
+                let expected: Vec<[f64; 2]> = (0..left.len())
+                    .map(|i| {
+                        let x: Vec<f64> = inputs.iter().map(|x| x[i]).collect();
+                        per_sample.process_2ch(&x)
+                    })
+                    .collect();
+
+                let mut out_1 = vec![0.0; left.len()];
+                let mut out_2 = vec![0.0; left.len()];
+                block.process_block(&inputs, &mut [&mut out_1, &mut out_2]);
+                assert_eq!(expected.iter().map(|y| y[0]).collect::<Vec<_>>(), out_1);
+                assert_eq!(expected.iter().map(|y| y[1]).collect::<Vec<_>>(), out_2);
+            }
+        }
+    }
+
+    #[test]
+    fn saturation() {
+        let params = PlateParams {
+            decay: 0.9,
+            ..PlateParams::default()
+        };
+        let saturated = PlateParams {
+            drive: 16.0,
+            character: 0.5,
+            ..params.clone()
+        };
+        let mut clean = Plate::<f64>::new(SAMPLE_RATE, 4095);
+        let mut plate = Plate::<f64>::new(SAMPLE_RATE, 4095);
+
+        // Quiet tails decay as without saturation.
+        clean.set_params(params);
+        plate.set_params(saturated);
+        for t in 0..50000 {
+            let x = if t == 0 { 1e-6 } else { 0.0 };
+            let [expected, y] = [clean.process_2ch(&[x]), plate.process_2ch(&[x])];
+            for (expected, y) in expected.iter().zip(y) {
+                assert!((expected - y).abs() <= 1e-3 * 1e-6);
+            }
+        }
+
+        // Loud ones are coloured, but decay as fast as without saturation.
+        clean.clear();
+        plate.clear();
+        let (mut clean_tail, mut tail) = (vec![], vec![]);
+        for t in 0..150000 {
+            let x = if t < 5000 {
+                ((t * 7919) % 101) as f64 / 50.0 - 1.0
+            } else {
+                0.0
+            };
+            let [expected, y] = [clean.process_2ch(&[x]), plate.process_2ch(&[x])];
+            assert!(y.iter().all(|y| y.is_finite()));
+            if t >= 5000 {
+                clean_tail.push(expected[0] * expected[0] + expected[1] * expected[1]);
+                tail.push(y[0] * y[0] + y[1] * y[1]);
+            }
+        }
+        assert_ne!(clean_tail, tail);
+        let (clean_rt60, rt60) = (rt60(&clean_tail), rt60(&tail));
+        assert!(
+            (rt60 / clean_rt60 - 1.0).abs() < 0.05,
+            "{rt60} against {clean_rt60}"
+        );
+    }
+
+    /// Decay time in samples from the energy of a tail, extrapolated from its fall
+    /// from -5 to -25 dB by backward integration.
+    fn rt60(energy: &[f64]) -> f64 {
+        let mut remaining = energy
+            .iter()
+            .rev()
+            .scan(0.0, |sum, e| {
+                *sum += e;
+                Some(*sum)
+            })
+            .collect::<Vec<_>>();
+        remaining.reverse();
+        let level = |db: f64| {
+            remaining
+                .iter()
+                .position(|e| 10.0 * (e / remaining[0]).log10() < db)
+                .unwrap() as f64
+        };
+        3.0 * (level(-25.0) - level(-5.0))
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn at_sample_rate() {
+        let params: PlateParams<f32> = PlateParams {
+            predelay: 100,
+            bandwidth: 0.6,
+            damping: 0.3,
+            reverse_window: 1000,
+            ..PlateParams::default()
+        };
+        assert_eq!(params, params.clone().at_sample_rate(SAMPLE_RATE));
+        let double = params.clone().at_sample_rate(2.0 * SAMPLE_RATE);
+        assert_eq!((200, 2000), (double.predelay, double.reverse_window));
+        // Two steps at twice the rate make one at the native rate.
+        assert!(((1.0 - double.bandwidth).powi(2) - 0.4).abs() < 1e-6);
+        assert!((double.damping.powi(2) - 0.3).abs() < 1e-6);
+        assert_eq!(params.decay, double.decay);
+    }
+
+    #[test]
+    fn lengths() {
+        let lengths = PlateLengths::new(2.0 * SAMPLE_RATE);
+        assert_eq!(2 * DELAY_1, lengths.delay_1);
+        assert_eq!(2 * TAPS[0], lengths.taps[0]);
+        assert_eq!(PlateLengths::default().delay_1, DELAY_1);
+    }
+
+    #[test]
+    fn required_lengths() {
+        let lengths = PlateLengths::new(48000.0);
+        let required = PlateBuffers::required_lengths(&lengths, 10, REVERSE, SHIMMER);
+        assert_eq!(11, required.predelay);
+        assert_eq!(lengths.delay_2 + 1, required.delay_2);
+        assert_eq!(
+            lengths.decay_diffusion_1_2 + EXCURSION + 1,
+            required.decay_diffusion_1_2
+        );
+        assert_eq!(required.to_array().iter().sum::<usize>(), required.total());
+    }
+
+    #[test]
+    fn burst() {
+        let mut plate = Plate::<f64>::new(SAMPLE_RATE, 4095);
+
+        for t in 0..500 {
+            let burst = if t < 50 {
+                (core::f64::consts::TAU * t as f64 / 50.0).sin()
+            } else {
+                0.0
+            };
+            let y = plate.process_2ch(&[burst]);
+            println!("{},{},{},{}", t, burst, y[0], y[1]);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Plate buffers carved out of a single allocation.
+//!
+//! [`PlateArena`] holds the memory of every delay line, filter state and reverser
+//! of a plate back to back, and [`OwnedPlate`] keeps a [`Plate`] together with its arena
+//! so that it can live as long as needed without leaking the memory.
+
+use super::*;
+use alloc::{boxed::Box, vec};
+use core::{mem::ManuallyDrop, ptr::NonNull};
+
+/// One contiguous block of memory holding all buffers of a plate.
+pub struct PlateArena<V> {
+    memory: Box<[V]>,
+    sizes: PlateBuffers<usize, ()>,
+}
+
+impl<V> PlateArena<V>
+where
+    V: num_traits::Zero + Clone,
+{
+    /// Allocates zeroed buffers for a plate running at `lengths`,
+    /// with a pre delay of up to `max_predelay` samples, the reverse mode and the shimmer.
+    pub fn new(lengths: &PlateLengths, max_predelay: usize) -> Self {
+        let sizes = PlateBuffers::required_lengths(lengths, max_predelay, REVERSE, SHIMMER);
+        Self {
+            memory: vec![V::zero(); sizes.total()].into_boxed_slice(),
+            sizes,
+        }
+    }
+}
+
+impl<V> PlateArena<V> {
+    /// Splits the memory into the buffers of a plate.
+    pub fn buffers(&mut self) -> PlateBuffers<&mut [V], V> {
+        PlateBuffers::split(&mut self.memory, &self.sizes)
+    }
+}
+
+impl<V> PlateArena<V>
+where
+    V: num_traits::Num + num_traits::Signed + num_traits::FromPrimitive + Clone,
+{
+    /// Builds a plate running at the sample rate of `lengths`,
+    /// which can't be longer than the ones the arena was allocated for.
+    pub fn build_with_lengths(&mut self, lengths: PlateLengths) -> Plate<'_, V> {
+        self.buffers().into_plate(lengths)
+    }
+}
+
+/// A [`Plate`] owning its [`PlateArena`], which is freed when the plate is dropped.
+///
+/// The plate can't be borrowed out, as it would outlive the arena,
+/// so its methods are forwarded instead.
+pub struct OwnedPlate<V: 'static> {
+    plate: ManuallyDrop<Plate<'static, V>>,
+    arena: NonNull<PlateArena<V>>,
+}
+
+// The plate only refers to the arena, which moves along with it.
+unsafe impl<V: Send> Send for OwnedPlate<V> {}
+
+impl<V> OwnedPlate<V>
+where
+    V: num_traits::Num + num_traits::Signed + num_traits::FromPrimitive + Clone,
+{
+    /// Builds a plate running at the sample rate of `lengths` from `arena`,
+    /// which has to be allocated for the same or longer lengths.
+    pub fn new(arena: PlateArena<V>, lengths: PlateLengths) -> Self {
+        let arena = NonNull::from(Box::leak(Box::new(arena)));
+        // The arena is only freed on drop, after the plate borrowing it,
+        // and is never accessed otherwise.
+        let plate = unsafe { &mut *arena.as_ptr() }.build_with_lengths(lengths);
+        Self {
+            plate: ManuallyDrop::new(plate),
+            arena,
+        }
+    }
+}
+
+impl<V> Plate<'static, V>
+where
+    V: num_traits::Num + num_traits::Signed + num_traits::FromPrimitive + Clone,
+{
+    /// Allocates a plate running at `sample_rate`,
+    /// with a pre delay of up to `max_predelay` samples.
+    ///
+    /// ```rust
+    /// # use oxide_plate_dsp::plate::*;
+    /// let mut plate = Plate::new(48000.0, 4800);
+    /// plate.set_params(PlateParams::<f32>::default());
+    /// let [left, right] = plate.process_2ch(&[1.0]);
+    /// ```
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(sample_rate: f64, max_predelay: usize) -> OwnedPlate<V> {
+        let lengths = PlateLengths::new(sample_rate);
+        OwnedPlate::new(PlateArena::new(&lengths, max_predelay), lengths)
+    }
+}
+
+impl<V> Drop for OwnedPlate<V> {
+    fn drop(&mut self) {
+        unsafe {
+            ManuallyDrop::drop(&mut self.plate);
+            drop(Box::from_raw(self.arena.as_ptr()));
+        }
+    }
+}
+
+impl<V> OwnedPlate<V>
+where
+    V: num_traits::Num + num_traits::One + num_traits::Signed + num_traits::FromPrimitive + Clone,
+{
+    /// See [`Plate::set_params`].
+    pub fn set_params(&mut self, params: PlateParams<V>) {
+        self.plate.set_params(params)
+    }
+
+    /// See [`Plate::latency`].
+    pub fn latency(&self) -> usize {
+        self.plate.latency()
+    }
+
+    /// See [`Plate::process`].
+    pub fn process(&mut self, x: &[V]) {
+        self.plate.process(x)
+    }
+}
+
+impl<V> OwnedPlate<V>
+where
+    V: num_traits::Num
+        + num_traits::NumAssign
+        + num_traits::Signed
+        + num_traits::FromPrimitive
+        + Clone,
+{
+    /// See [`Plate::process_2ch`].
+    pub fn process_2ch(&mut self, x: &[V]) -> [V; 2] {
+        self.plate.process_2ch(x)
+    }
+
+    /// See [`Plate::process_block`].
+    pub fn process_block(&mut self, inputs: &[&[V]], outputs: &mut [&mut [V]]) {
+        self.plate.process_block(inputs, outputs)
+    }
+}
+
+impl<V> OwnedPlate<V>
+where
+    V: Default + num_traits::One + Clone,
+{
+    /// See [`Plate::clear`].
+    pub fn clear(&mut self) {
+        self.plate.clear()
+    }
+
+    /// See [`Plate::set_lengths`].
+    pub fn set_lengths(&mut self, lengths: PlateLengths) {
+        self.plate.set_lengths(lengths)
+    }
+}
+
+impl<V> OwnedPlate<V>
+where
+    V: num_traits::float::FloatCore
+        + num_traits::NumAssign
+        + num_traits::Signed
+        + num_traits::FromPrimitive
+        + Default,
+{
+    /// See [`Plate::process_2ch_checked`].
+    pub fn process_2ch_checked(&mut self, x: &[V]) -> [V; 2] {
+        self.plate.process_2ch_checked(x)
+    }
+}
+
+impl<V> OwnedPlate<V>
+where
+    V: num_traits::Num
+        + num_traits::NumAssign
+        + num_traits::Signed
+        + num_traits::FromPrimitive
+        + PartialOrd
+        + Clone,
+{
+    /// See [`Plate::impulse_response`].
+    pub fn impulse_response(
+        &mut self,
+        threshold: V,
+        max_length: usize,
+    ) -> ImpulseTail<'_, 'static, V> {
+        self.plate.impulse_response(threshold, max_length)
+    }
+
+    /// See [`Plate::tail`].
+    pub fn tail(&mut self, threshold: V, max_length: usize) -> ImpulseTail<'_, 'static, V> {
+        self.plate.tail(threshold, max_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owned() {
+        let lengths = PlateLengths::new(48000.0);
+        let params = PlateParams {
+            predelay: 100,
+            shimmer: 0.2,
+            reverse: true,
+            reverse_window: 1000,
+            ..PlateParams::default()
+        };
+
+        let mut arena = PlateArena::new(&lengths, 100);
+        let mut reference = arena.build_with_lengths(lengths.clone());
+        reference.set_params(params.clone());
+
+        let mut plate = Plate::new(48000.0, 100);
+        plate.set_params(params);
+        assert_eq!(reference.latency(), plate.latency());
+
+        for t in 0..20000 {
+            let x = [((t * 7919) % 101) as f32 / 50.0 - 1.0];
+            assert_eq!(reference.process_2ch(&x), plate.process_2ch(&x));
+        }
+        plate.clear();
+        assert_eq!([0.0, 0.0], plate.process_2ch(&[0.0]));
+    }
+
+    #[test]
+    fn impulse_response() {
+        let lengths = PlateLengths::new(48000.0);
+        let mut arena = PlateArena::new(&lengths, 0);
+        let mut reference = arena.build_with_lengths(lengths);
+        reference.set_params(PlateParams::<f32>::default());
+        let mut plate = Plate::new(48000.0, 0);
+        plate.set_params(PlateParams::default());
+        assert!(reference
+            .impulse_response(1e-3, 48000)
+            .eq(plate.impulse_response(1e-3, 48000)));
+    }
+
+    #[test]
+    fn set_lengths() {
+        let arena = PlateArena::new(&PlateLengths::new(96000.0), 0);
+        let mut plate = OwnedPlate::new(arena, PlateLengths::new(48000.0));
+        plate.set_params(PlateParams::default());
+        plate.process_2ch(&[1.0]);
+
+        // Runs like a plate allocated for the higher rate.
+        let mut reference = Plate::new(96000.0, 0);
+        reference.set_params(PlateParams::default());
+        plate.set_lengths(PlateLengths::new(96000.0));
+        plate.set_params(PlateParams::default());
+        for t in 0..20000 {
+            let x = [((t * 7919) % 101) as f32 / 50.0 - 1.0];
+            assert_eq!(reference.process_2ch(&x), plate.process_2ch(&x));
+        }
+    }
+}
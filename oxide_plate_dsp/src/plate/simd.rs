@@ -0,0 +1,599 @@
+//! A single precision plate whose two tank halves run side by side in SIMD lanes.
+//!
+//! Lane 0 carries the first half of the tank (decay diffusion 1-1, delay 1, damping 1,
+//! decay diffusion 2-1, delay 2) and lane 1 the second half. The matching delay lines of
+//! both halves are interleaved into one buffer of [`f32x4`] with a shared write head,
+//! and the left and right output taps are summed in lanes 0 and 1 the same way.
+//!
+//! Every lane performs the same operations in the same order as [`Plate`](super::Plate),
+//! so the output is identical to a `Plate<f32>` with the shimmer and the reverse mode.
+
+use super::*;
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::{mem::ManuallyDrop, ptr::NonNull};
+use wide::f32x4;
+
+pub struct SimdPlateBuffers<T, L> {
+    pub predelay: T,
+    pub prefilter: T,
+
+    pub input_diffusion_1_1: T,
+    pub input_diffusion_1_2: T,
+    pub input_diffusion_2_1: T,
+    pub input_diffusion_2_2: T,
+
+    pub shimmer: T,
+
+    /// Decay diffusions 1-1 and 1-2.
+    pub decay_diffusion_1: L,
+    /// Delays 1 and 3.
+    pub delay_1: L,
+    /// Decay diffusions 2-1 and 2-2.
+    pub decay_diffusion_2: L,
+    /// Delays 2 and 4.
+    pub delay_2: L,
+
+    pub reverse_1: T,
+    pub reverse_2: T,
+}
+
+impl SimdPlateBuffers<Vec<f32>, Vec<f32x4>> {
+    /// Allocates buffers for a plate running at `lengths`,
+    /// with a pre delay of up to `max_predelay` samples.
+    pub fn new(lengths: &PlateLengths, max_predelay: usize) -> Self {
+        let pair = |a: usize, b: usize| vec![f32x4::ZERO; a.max(b) + 1];
+        Self {
+            predelay: vec![0.0; max_predelay + 1],
+            prefilter: vec![0.0],
+            input_diffusion_1_1: vec![0.0; lengths.input_diffusion_1_1 + 1],
+            input_diffusion_1_2: vec![0.0; lengths.input_diffusion_1_2 + 1],
+            input_diffusion_2_1: vec![0.0; lengths.input_diffusion_2_1 + 1],
+            input_diffusion_2_2: vec![0.0; lengths.input_diffusion_2_2 + 1],
+            shimmer: vec![0.0; SHIMMER + 2],
+            decay_diffusion_1: pair(
+                lengths.decay_diffusion_1_1 + EXCURSION,
+                lengths.decay_diffusion_1_2 + EXCURSION,
+            ),
+            delay_1: pair(lengths.delay_1, lengths.delay_3),
+            decay_diffusion_2: pair(lengths.decay_diffusion_2_1, lengths.decay_diffusion_2_2),
+            delay_2: pair(lengths.delay_2, lengths.delay_4),
+            reverse_1: vec![0.0; 2 * REVERSE],
+            reverse_2: vec![0.0; 2 * REVERSE],
+        }
+    }
+}
+
+impl<T, L> SimdPlateBuffers<T, L>
+where
+    T: AsMut<[f32]>,
+    L: AsMut<[f32x4]>,
+{
+    pub fn build(&mut self) -> SimdPlate<'_> {
+        self.build_with_lengths(PlateLengths::default())
+    }
+
+    /// Builds a plate running at the sample rate of `lengths`.
+    ///
+    /// Each interleaved buffer has to be longer than the longer of its two scaled lengths,
+    /// and the decay diffusions 1 additionally by [`EXCURSION`].
+    pub fn build_with_lengths(&mut self, lengths: PlateLengths) -> SimdPlate<'_> {
+        SimdPlate {
+            predelay: Delay::new(self.predelay.as_mut()),
+            predelay_length: 1,
+            prefilter: IIR::new(self.prefilter.as_mut()),
+            input_diffusion_1_1: APF::new(self.input_diffusion_1_1.as_mut()),
+            input_diffusion_1_2: APF::new(self.input_diffusion_1_2.as_mut()),
+            input_diffusion_2_1: APF::new(self.input_diffusion_2_1.as_mut()),
+            input_diffusion_2_2: APF::new(self.input_diffusion_2_2.as_mut()),
+            shimmer: PitchShifter::new(self.shimmer.as_mut()),
+            shimmer_amount: 0.0,
+            tank: f32x4::ZERO,
+            decay_diffusion_1: PairedAPF::new(
+                self.decay_diffusion_1.as_mut(),
+                [lengths.decay_diffusion_1_1, lengths.decay_diffusion_1_2],
+            ),
+            delay_1: PairedDelay::new(self.delay_1.as_mut()),
+            damping: f32x4::ZERO,
+            damping_a: f32x4::ZERO,
+            damping_b: f32x4::ONE,
+            saturator_1: Saturator::new(0.0, 0.0, 0.0),
+            saturator_2: Saturator::new(0.0, 0.0, 0.0),
+            decay: f32x4::ZERO,
+            decay_diffusion_2: PairedAPF::new(
+                self.decay_diffusion_2.as_mut(),
+                [lengths.decay_diffusion_2_1, lengths.decay_diffusion_2_2],
+            ),
+            delay_2: PairedDelay::new(self.delay_2.as_mut()),
+            reverse_1: Reverser::new(self.reverse_1.as_mut()),
+            reverse_2: Reverser::new(self.reverse_2.as_mut()),
+            reverse: false,
+            lengths,
+        }
+    }
+}
+
+/// Two delay lines sharing one write head, one per lane.
+struct PairedDelay<'a> {
+    head: usize,
+    buffer: &'a mut [f32x4],
+}
+
+impl<'a> PairedDelay<'a> {
+    fn new(buffer: &'a mut [f32x4]) -> Self {
+        if buffer.is_empty() {
+            panic!("buffer is empty");
+        }
+        Self { head: 0, buffer }
+    }
+
+    fn write(&mut self, value: f32x4) {
+        self.buffer[self.head] = value;
+        self.head = (self.head + 1) % self.buffer.len();
+    }
+
+    /// Reads `lane` with delay, where `delay = 1` is the last written value.
+    fn read(&self, delay: usize, lane: usize) -> f32 {
+        let len = self.buffer.len();
+        let index = (self.head + len - delay % len) % len;
+        self.buffer[index].as_array_ref()[lane]
+    }
+
+    /// Reads lane 0 and lane 1 with their own delays.
+    fn read_pair(&self, delays: [usize; 2]) -> f32x4 {
+        f32x4::new([self.read(delays[0], 0), self.read(delays[1], 1), 0.0, 0.0])
+    }
+
+    fn clear(&mut self) {
+        self.buffer.fill(f32x4::ZERO);
+    }
+}
+
+/// Two all-pass filters sharing their coefficients, one per lane.
+struct PairedAPF<'a> {
+    delay_line: PairedDelay<'a>,
+    lengths: [usize; 2],
+    delays: [usize; 2],
+    a: f32x4,
+    b: f32x4,
+}
+
+impl<'a> PairedAPF<'a> {
+    fn new(buffer: &'a mut [f32x4], lengths: [usize; 2]) -> Self {
+        Self {
+            delay_line: PairedDelay::new(buffer),
+            lengths,
+            delays: [1, 1],
+            a: f32x4::ZERO,
+            b: f32x4::ZERO,
+        }
+    }
+
+    fn set_params(&mut self, a: f32, b: f32, modulation: isize) {
+        self.a = f32x4::splat(a);
+        self.b = f32x4::splat(b);
+        self.delays = self
+            .lengths
+            .map(|length| (length as isize + modulation) as usize);
+    }
+
+    fn tick(&mut self, x: f32x4) -> f32x4 {
+        let z = self.delay_line.read_pair(self.delays);
+        let x = x - self.b * z;
+        let y = self.a * x + z;
+        self.delay_line.write(x);
+        y
+    }
+}
+
+pub struct SimdPlate<'a> {
+    lengths: PlateLengths,
+
+    predelay: Delay<'a, f32>,
+    predelay_length: usize,
+    prefilter: IIR<'a, f32, 1>,
+
+    input_diffusion_1_1: APF<'a, f32>,
+    input_diffusion_1_2: APF<'a, f32>,
+    input_diffusion_2_1: APF<'a, f32>,
+    input_diffusion_2_2: APF<'a, f32>,
+
+    shimmer: PitchShifter<'a, f32>,
+    shimmer_amount: f32,
+
+    /// Feedback into each half, already crossed over.
+    tank: f32x4,
+
+    decay_diffusion_1: PairedAPF<'a>,
+    delay_1: PairedDelay<'a>,
+    damping: f32x4,
+    damping_a: f32x4,
+    damping_b: f32x4,
+    saturator_1: Saturator<f32>,
+    saturator_2: Saturator<f32>,
+    decay: f32x4,
+    decay_diffusion_2: PairedAPF<'a>,
+    delay_2: PairedDelay<'a>,
+
+    reverse_1: Reverser<'a, f32>,
+    reverse_2: Reverser<'a, f32>,
+    reverse: bool,
+}
+
+impl<'a> SimdPlate<'a> {
+    pub fn set_params(&mut self, params: PlateParams<f32>) {
+        self.predelay_length = params.predelay;
+        self.prefilter
+            .set_params([1.0 - params.bandwidth], params.bandwidth);
+
+        self.input_diffusion_1_1.set_params(
+            params.input_diffusion_1,
+            params.input_diffusion_1,
+            self.lengths.input_diffusion_1_1.try_into().unwrap(),
+        );
+        self.input_diffusion_1_2.set_params(
+            params.input_diffusion_1,
+            params.input_diffusion_1,
+            self.lengths.input_diffusion_1_2.try_into().unwrap(),
+        );
+        self.input_diffusion_2_1.set_params(
+            params.input_diffusion_2,
+            params.input_diffusion_2,
+            self.lengths.input_diffusion_2_1.try_into().unwrap(),
+        );
+        self.input_diffusion_2_2.set_params(
+            params.input_diffusion_2,
+            params.input_diffusion_2,
+            self.lengths.input_diffusion_2_2.try_into().unwrap(),
+        );
+
+        self.decay_diffusion_1.set_params(
+            -params.decay_diffusion_1,
+            -params.decay_diffusion_1,
+            params.decay_modulation,
+        );
+        self.decay_diffusion_2
+            .set_params(params.decay_diffusion_2, params.decay_diffusion_2, 0);
+
+        self.damping_a = f32x4::splat(params.damping);
+        self.damping_b = f32x4::splat(1.0 - params.damping);
+        self.decay = f32x4::splat(params.decay);
+        let smoothing = (1.0 - 1.0 / (SATURATOR_LEVEL * self.lengths.sample_rate)) as f32;
+        for saturator in [&mut self.saturator_1, &mut self.saturator_2] {
+            saturator.set_params(params.drive, params.character, smoothing);
+        }
+
+        self.shimmer
+            .set_ratio(interval_to_ratio(params.shimmer_interval));
+        self.shimmer_amount = params.shimmer;
+
+        self.reverse = params.reverse;
+        self.reverse_1
+            .set_window(params.reverse_window.try_into().unwrap());
+        self.reverse_2
+            .set_window(params.reverse_window.try_into().unwrap());
+    }
+
+    /// Returns the latency added by the reverse mode, in samples.
+    pub fn latency(&self) -> usize {
+        if self.reverse {
+            self.reverse_1.window()
+        } else {
+            0
+        }
+    }
+
+    pub fn process_2ch(&mut self, x: &[f32]) -> [f32; 2] {
+        self.tick(mean(x));
+        self.output()
+    }
+
+    /// Like [`Plate::process_2ch_checked`].
+    pub fn process_2ch_checked(&mut self, x: &[f32]) -> [f32; 2] {
+        let y = if x.iter().all(|x| x.is_finite()) {
+            self.process_2ch(x)
+        } else {
+            self.process_2ch(&[0.0])
+        };
+        let tank = self.tank.to_array();
+        if y.iter().chain(&tank).all(|y| y.is_finite()) {
+            y
+        } else {
+            self.clear();
+            [0.0, 0.0]
+        }
+    }
+
+    /// Like [`Plate::process_block`].
+    ///
+    /// ## Panics
+    /// - If `inputs` is empty.
+    /// - If there are more than two outputs.
+    /// - If the channels differ in length.
+    pub fn process_block(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        if outputs.len() > 2 {
+            panic!("more than two outputs");
+        }
+        let len = inputs[0].len();
+        if inputs.iter().any(|x| x.len() != len) || outputs.iter().any(|y| y.len() != len) {
+            panic!("channels differ in length");
+        }
+
+        let scale = 1.0 / inputs.len() as f32;
+        for i in 0..len {
+            let x = inputs
+                .iter()
+                .map(|x| x[i] * scale)
+                .reduce(|acc, x| acc + x)
+                .unwrap();
+            self.tick(x);
+            let y = self.output();
+            for (output, y) in outputs.iter_mut().zip(y) {
+                output[i] = y;
+            }
+        }
+    }
+
+    /// Silences the plate by clearing every buffer.
+    pub fn clear(&mut self) {
+        self.predelay.clear();
+        self.prefilter.clear();
+        self.input_diffusion_1_1.clear();
+        self.input_diffusion_1_2.clear();
+        self.input_diffusion_2_1.clear();
+        self.input_diffusion_2_2.clear();
+        self.shimmer.clear();
+        self.tank = f32x4::ZERO;
+        self.decay_diffusion_1.delay_line.clear();
+        self.delay_1.clear();
+        self.damping = f32x4::ZERO;
+        self.saturator_1.clear();
+        self.saturator_2.clear();
+        self.decay_diffusion_2.delay_line.clear();
+        self.delay_2.clear();
+        self.reverse_1.clear();
+        self.reverse_2.clear();
+    }
+
+    /// See [`Plate::set_lengths`].
+    pub fn set_lengths(&mut self, lengths: PlateLengths) {
+        self.decay_diffusion_1.lengths = [lengths.decay_diffusion_1_1, lengths.decay_diffusion_1_2];
+        self.decay_diffusion_2.lengths = [lengths.decay_diffusion_2_1, lengths.decay_diffusion_2_2];
+        self.lengths = lengths;
+        self.clear();
+    }
+
+    fn tick(&mut self, x: f32) {
+        self.predelay.write(x);
+        let mut acc = *self.predelay.read(self.predelay_length.try_into().unwrap());
+
+        acc = self.prefilter.tick(acc);
+
+        acc = self.input_diffusion_1_1.tick(acc);
+        acc = self.input_diffusion_1_2.tick(acc);
+        acc = self.input_diffusion_2_1.tick(acc);
+        acc = self.input_diffusion_2_2.tick(acc);
+
+        let [tank_1, tank_2, ..] = self.tank.to_array();
+        let shimmer = self.shimmer_amount * self.shimmer.tick(0.5 * tank_1 + 0.5 * tank_2);
+        let keep = f32x4::splat(1.0 - self.shimmer_amount);
+
+        let mut tank = f32x4::splat(acc) + (keep * self.tank + f32x4::splat(shimmer));
+        tank = self.decay_diffusion_1.tick(tank);
+        self.delay_1.write(tank);
+        tank = self
+            .delay_1
+            .read_pair([self.lengths.delay_1, self.lengths.delay_3]);
+        if !self.saturator_1.is_bypassed() {
+            // Each half follows its own level, as in the scalar plate.
+            let [half_1, half_2, ..] = tank.to_array();
+            tank = f32x4::new([
+                self.saturator_1.tick(half_1),
+                self.saturator_2.tick(half_2),
+                0.0,
+                0.0,
+            ]);
+        }
+        self.damping = f32x4::ZERO + self.damping * self.damping_a + tank * self.damping_b;
+        tank = self.decay * self.damping;
+        tank = self.decay_diffusion_2.tick(tank);
+        self.delay_2.write(tank);
+        tank = self
+            .delay_2
+            .read_pair([self.lengths.delay_2, self.lengths.delay_4]);
+        tank = self.decay * tank;
+
+        let [half_1, half_2, ..] = tank.to_array();
+        self.tank = f32x4::new([half_2, half_1, 0.0, 0.0]);
+    }
+
+    /// Sums the output taps, left in lane 0 and right in lane 1.
+    fn output(&mut self) -> [f32; 2] {
+        let taps = &self.lengths.taps;
+        // Each tap of the left output has a counterpart on the right
+        // that reads the same line of the other half.
+        let cross = |line: &PairedDelay, lane: usize, left: usize, right: usize| {
+            f32x4::new([
+                line.read(taps[left], lane),
+                line.read(taps[right], 1 - lane),
+                0.0,
+                0.0,
+            ])
+        };
+        let delay_1 = &self.delay_1;
+        let decay_diffusion_2 = &self.decay_diffusion_2.delay_line;
+        let delay_2 = &self.delay_2;
+
+        let mut acc = cross(delay_1, 1, 0, 7);
+        acc += cross(delay_1, 1, 1, 8);
+        acc -= cross(decay_diffusion_2, 1, 2, 9);
+        acc += cross(delay_2, 1, 3, 10);
+        acc -= cross(delay_1, 0, 4, 11);
+        acc -= cross(decay_diffusion_2, 0, 5, 12);
+        acc -= cross(delay_2, 0, 6, 13);
+
+        let [left, right, ..] = acc.to_array();
+        // Keep recording while disabled so that switching on plays back the recent tail.
+        let left_reversed = self.reverse_1.tick(left);
+        let right_reversed = self.reverse_2.tick(right);
+        if self.reverse {
+            [left_reversed, right_reversed]
+        } else {
+            [left, right]
+        }
+    }
+}
+
+/// A [`SimdPlate`] owning its buffers, which are freed when the plate is dropped,
+/// like [`OwnedPlate`].
+pub struct OwnedSimdPlate {
+    plate: ManuallyDrop<SimdPlate<'static>>,
+    buffers: NonNull<SimdPlateBuffers<Vec<f32>, Vec<f32x4>>>,
+}
+
+// The plate only refers to the buffers, which move along with it.
+unsafe impl Send for OwnedSimdPlate {}
+
+impl OwnedSimdPlate {
+    /// Builds a plate running at the sample rate of `lengths` from `buffers`,
+    /// which have to be allocated for the same or longer lengths.
+    pub fn new(buffers: SimdPlateBuffers<Vec<f32>, Vec<f32x4>>, lengths: PlateLengths) -> Self {
+        let buffers = NonNull::from(Box::leak(Box::new(buffers)));
+        // The buffers are only freed on drop, after the plate borrowing them,
+        // and are never accessed otherwise.
+        let plate = unsafe { &mut *buffers.as_ptr() }.build_with_lengths(lengths);
+        Self {
+            plate: ManuallyDrop::new(plate),
+            buffers,
+        }
+    }
+
+    /// See [`SimdPlate::set_params`].
+    pub fn set_params(&mut self, params: PlateParams<f32>) {
+        self.plate.set_params(params)
+    }
+
+    /// See [`SimdPlate::latency`].
+    pub fn latency(&self) -> usize {
+        self.plate.latency()
+    }
+
+    /// See [`SimdPlate::process_2ch`].
+    pub fn process_2ch(&mut self, x: &[f32]) -> [f32; 2] {
+        self.plate.process_2ch(x)
+    }
+
+    /// See [`SimdPlate::process_2ch_checked`].
+    pub fn process_2ch_checked(&mut self, x: &[f32]) -> [f32; 2] {
+        self.plate.process_2ch_checked(x)
+    }
+
+    /// See [`SimdPlate::process_block`].
+    pub fn process_block(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        self.plate.process_block(inputs, outputs)
+    }
+
+    /// See [`SimdPlate::clear`].
+    pub fn clear(&mut self) {
+        self.plate.clear()
+    }
+
+    /// See [`SimdPlate::set_lengths`].
+    pub fn set_lengths(&mut self, lengths: PlateLengths) {
+        self.plate.set_lengths(lengths)
+    }
+}
+
+impl SimdPlate<'static> {
+    /// Allocates a plate running at `sample_rate`,
+    /// with a pre delay of up to `max_predelay` samples.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(sample_rate: f64, max_predelay: usize) -> OwnedSimdPlate {
+        let lengths = PlateLengths::new(sample_rate);
+        OwnedSimdPlate::new(SimdPlateBuffers::new(&lengths, max_predelay), lengths)
+    }
+}
+
+impl Drop for OwnedSimdPlate {
+    fn drop(&mut self) {
+        unsafe {
+            ManuallyDrop::drop(&mut self.plate);
+            drop(Box::from_raw(self.buffers.as_ptr()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_scalar() {
+        for sample_rate in [SAMPLE_RATE, 48000.0] {
+            let mut scalar = Plate::<f32>::new(sample_rate, 4095);
+            let mut simd = SimdPlate::new(sample_rate, 4095);
+
+            for (decay_modulation, shimmer, reverse, drive) in
+                [(0, 0.0, false, 0.0), (-7, 0.3, true, 4.0)]
+            {
+                let params = PlateParams {
+                    predelay: 100,
+                    decay: 0.9,
+                    decay_modulation,
+                    drive,
+                    character: 0.5,
+                    shimmer,
+                    reverse,
+                    reverse_window: 1000,
+                    ..PlateParams::default()
+                };
+                scalar.set_params(params.clone());
+                simd.set_params(params);
+                for t in 0..20000 {
+                    let x = [((t * 7919) % 101) as f32 / 50.0 - 1.0, 0.25];
+                    let x = if t < 5000 { &x[..] } else { &[0.0][..] };
+                    assert_eq!(scalar.process_2ch(x), simd.process_2ch(x));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn block_and_clear() {
+        let lengths = PlateLengths::default();
+        let mut buffers = SimdPlateBuffers::new(&lengths, 4095);
+        let mut plate = buffers.build();
+        plate.set_params(PlateParams::default());
+
+        let input: Vec<f32> = (0..3000).map(|t| if t == 0 { 1.0 } else { 0.0 }).collect();
+        let expected: Vec<[f32; 2]> = input.iter().map(|x| plate.process_2ch(&[*x])).collect();
+
+        plate.clear();
+        let mut left = vec![0.0; input.len()];
+        let mut right = vec![0.0; input.len()];
+        plate.process_block(&[&input], &mut [&mut left, &mut right]);
+        assert_eq!(expected.iter().map(|y| y[0]).collect::<Vec<_>>(), left);
+        assert_eq!(expected.iter().map(|y| y[1]).collect::<Vec<_>>(), right);
+
+        let y = plate.process_2ch_checked(&[f32::NAN]);
+        assert!(y.iter().all(|y| y.is_finite()));
+    }
+
+    #[test]
+    fn set_lengths() {
+        let lengths = PlateLengths::new(2.0 * SAMPLE_RATE);
+        let mut simd =
+            OwnedSimdPlate::new(SimdPlateBuffers::new(&lengths, 0), PlateLengths::default());
+        simd.set_lengths(lengths);
+        let mut scalar = Plate::<f32>::new(2.0 * SAMPLE_RATE, 0);
+        let params = PlateParams {
+            decay_modulation: 8,
+            ..PlateParams::default()
+        };
+        scalar.set_params(params.clone());
+        simd.set_params(params);
+        for t in 0..20000 {
+            let x = [((t * 7919) % 101) as f32 / 50.0 - 1.0];
+            assert_eq!(scalar.process_2ch(&x), simd.process_2ch(&x));
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Plate buffers in an array sized at compile time, for targets without a heap.
+
+use super::*;
+
+/// Number of samples a [`StaticPlateBuffers`] needs for plates running at up to
+/// `max_sample_rate`, with the pre delay, reverse and shimmer windows of
+/// [`PlateBuffers::required_lengths`].
+pub const fn required_size(
+    max_sample_rate: f64,
+    max_predelay: usize,
+    max_reverse_window: usize,
+    shimmer_window: usize,
+) -> usize {
+    PlateBuffers::required_lengths(
+        &PlateLengths::new(max_sample_rate),
+        max_predelay,
+        max_reverse_window,
+        shimmer_window,
+    )
+    .total()
+}
+
+/// All buffers of a plate in one array of `N` samples,
+/// which can live in a `static` or on the stack without allocating.
+///
+/// ```rust
+/// # use oxide_plate_dsp::plate::*;
+/// // Without the reverse mode, which would take most of the memory.
+/// const SIZE: usize = required_size(48000.0, 0, 0, SHIMMER);
+///
+/// static mut BUFFERS: StaticPlateBuffers<f32, SIZE> = StaticPlateBuffers::new(0.0);
+///
+/// // Only borrowed once, at start up.
+/// let buffers = unsafe { &mut *core::ptr::addr_of_mut!(BUFFERS) };
+/// let mut plate = buffers.build_with_lengths(PlateLengths::new(48000.0), 0, 0, SHIMMER);
+/// plate.set_params(PlateParams::default());
+/// let [left, right] = plate.process_2ch(&[1.0]);
+/// ```
+pub struct StaticPlateBuffers<T, const N: usize> {
+    memory: [T; N],
+}
+
+impl<T, const N: usize> StaticPlateBuffers<T, N>
+where
+    T: Copy,
+{
+    /// Creates buffers filled with `zero`.
+    pub const fn new(zero: T) -> Self {
+        Self { memory: [zero; N] }
+    }
+}
+
+impl<T, const N: usize> StaticPlateBuffers<T, N>
+where
+    T: num_traits::Num + num_traits::Signed + num_traits::FromPrimitive + Clone,
+{
+    /// Builds a plate running at the sample rate of `lengths`,
+    /// with the pre delay, reverse and shimmer windows of [`PlateBuffers::required_lengths`].
+    ///
+    /// ## Panics
+    /// - If `N` is less than the [required size](required_size).
+    pub fn build_with_lengths(
+        &mut self,
+        lengths: PlateLengths,
+        max_predelay: usize,
+        max_reverse_window: usize,
+        shimmer_window: usize,
+    ) -> Plate<'_, T> {
+        let sizes = PlateBuffers::required_lengths(
+            &lengths,
+            max_predelay,
+            max_reverse_window,
+            shimmer_window,
+        );
+        PlateBuffers::split(&mut self.memory, &sizes).into_plate(lengths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Without the reverse mode and the shimmer.
+    const SIZE: usize = required_size(48000.0, 100, 0, 0);
+
+    static mut BUFFERS: StaticPlateBuffers<f32, SIZE> = StaticPlateBuffers::new(0.0);
+
+    #[test]
+    fn static_plate() {
+        let lengths = PlateLengths::new(48000.0);
+        let mut reference = Plate::<f32>::new(48000.0, 100);
+        let params = PlateParams {
+            predelay: 100,
+            ..PlateParams::default()
+        };
+        reference.set_params(params.clone());
+
+        let buffers = unsafe { &mut *core::ptr::addr_of_mut!(BUFFERS) };
+        let mut plate = buffers.build_with_lengths(lengths.clone(), 100, 0, 0);
+        // Left out, so they have no effect.
+        plate.set_params(PlateParams {
+            shimmer: 0.5,
+            reverse: true,
+            ..params
+        });
+        assert_eq!(0, plate.latency());
+        for t in 0..10000 {
+            let x = [((t * 7919) % 101) as f32 / 50.0 - 1.0];
+            assert_eq!(reference.process_2ch(&x), plate.process_2ch(&x));
+        }
+
+        // Lower sample rates fit into the same buffers.
+        let mut plate = buffers.build_with_lengths(PlateLengths::new(44100.0), 100, 0, 0);
+        plate.process_2ch(&[1.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_short() {
+        let mut buffers = StaticPlateBuffers::<f32, 1024>::new(0.0);
+        buffers.build_with_lengths(PlateLengths::default(), 0, 0, 0);
+    }
+}
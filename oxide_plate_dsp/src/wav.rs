@@ -0,0 +1,184 @@
+//! Reading and writing WAV files, and rendering a plate's impulse response into one.
+
+use crate::plate::*;
+use std::path::Path;
+
+pub use hound::Error;
+
+/// Sample format of written WAV files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    Int16,
+    Int24,
+    Float32,
+}
+
+/// Audio with one buffer per channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Audio {
+    pub channels: Vec<Vec<f32>>,
+    pub sample_rate: u32,
+}
+
+impl Audio {
+    /// Reads a WAV file, scaling integer samples to `-1..1`.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut reader = hound::WavReader::open(path)?;
+        let spec = reader.spec();
+        let samples: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+            hound::SampleFormat::Int => {
+                let scale = 1.0 / (1i64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .samples::<i32>()
+                    .map(|x| x.map(|x| x as f32 * scale))
+                    .collect::<Result<_, _>>()?
+            }
+        };
+        let channels = (0..spec.channels as usize)
+            .map(|channel| {
+                samples
+                    .iter()
+                    .skip(channel)
+                    .step_by(spec.channels as usize)
+                    .copied()
+                    .collect()
+            })
+            .collect();
+        Ok(Self {
+            channels,
+            sample_rate: spec.sample_rate,
+        })
+    }
+
+    /// Writes a WAV file, clipping integer samples to `-1..1`.
+    pub fn write(&self, path: impl AsRef<Path>, format: SampleFormat) -> Result<(), Error> {
+        let (bits_per_sample, sample_format) = match format {
+            SampleFormat::Int16 => (16, hound::SampleFormat::Int),
+            SampleFormat::Int24 => (24, hound::SampleFormat::Int),
+            SampleFormat::Float32 => (32, hound::SampleFormat::Float),
+        };
+        let spec = hound::WavSpec {
+            channels: self.channels.len() as u16,
+            sample_rate: self.sample_rate,
+            bits_per_sample,
+            sample_format,
+        };
+        let mut writer = hound::WavWriter::create(path, spec)?;
+        let scale = ((1i64 << (bits_per_sample - 1)) - 1) as f32;
+        for n in 0..self.len() {
+            for channel in self.channels.iter() {
+                match sample_format {
+                    hound::SampleFormat::Float => writer.write_sample(channel[n])?,
+                    hound::SampleFormat::Int => {
+                        writer.write_sample((channel[n].clamp(-1.0, 1.0) * scale).round() as i32)?
+                    }
+                }
+            }
+        }
+        writer.finalize()
+    }
+
+    /// Renders the stereo response of a plate running at `sample_rate` to a unit impulse,
+    /// until it stays below `threshold` or reaches `max_length` samples.
+    ///
+    /// `params` are those of a plate at [`SAMPLE_RATE`], and are
+    /// [scaled](PlateParams::at_sample_rate) to `sample_rate` along with the delay lengths.
+    pub fn impulse_response(
+        params: PlateParams<f32>,
+        sample_rate: u32,
+        threshold: f32,
+        max_length: usize,
+    ) -> Self {
+        let params = params.at_sample_rate(sample_rate as f64);
+        let mut plate = Plate::new(sample_rate as f64, params.predelay);
+        plate.set_params(params);
+        let mut channels = vec![Vec::new(), Vec::new()];
+        for [left, right] in plate.impulse_response(threshold, max_length) {
+            channels[0].push(left);
+            channels[1].push(right);
+        }
+        Self {
+            channels,
+            sample_rate,
+        }
+    }
+
+    /// Length in samples.
+    pub fn len(&self) -> usize {
+        self.channels.first().map_or(0, Vec::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_read() {
+        let audio = Audio {
+            channels: vec![vec![0.5, -0.25, 0.0], vec![1.0, -1.0, 0.125]],
+            sample_rate: 44100,
+        };
+        let path = std::env::temp_dir().join("oxide_plate_write_and_read.wav");
+        for format in [
+            SampleFormat::Int16,
+            SampleFormat::Int24,
+            SampleFormat::Float32,
+        ] {
+            audio.write(&path, format).unwrap();
+            let read = Audio::read(&path).unwrap();
+            assert_eq!(audio.sample_rate, read.sample_rate);
+            for (expected, actual) in audio.channels.iter().zip(&read.channels) {
+                for (x, y) in expected.iter().zip(actual) {
+                    assert!((x - y).abs() < 1e-4, "{format:?}: {x} != {y}");
+                }
+            }
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn impulse_response() {
+        let params = PlateParams {
+            predelay: 100,
+            bandwidth: 0.6,
+            damping: 0.3,
+            decay: 0.7,
+            ..PlateParams::default()
+        };
+        let ir = Audio::impulse_response(params.clone(), 96000, 1e-4, 96000 * 10);
+        assert_eq!(96000, ir.sample_rate);
+        assert!(ir.len() < 96000 * 10);
+
+        // The same as a plate set up at the rate by hand.
+        let scaled = params.clone().at_sample_rate(96000.0);
+        let mut plate = Plate::new(96000.0, scaled.predelay);
+        plate.set_params(scaled.clone());
+        let expected: Vec<[f32; 2]> = plate.impulse_response(1e-4, 96000 * 10).collect();
+        assert_eq!(
+            expected.iter().map(|y| y[0]).collect::<Vec<_>>(),
+            ir.channels[0]
+        );
+        assert_eq!(
+            expected.iter().map(|y| y[1]).collect::<Vec<_>>(),
+            ir.channels[1]
+        );
+
+        // Nothing comes out before the scaled pre delay.
+        assert!(scaled.predelay > 300);
+        for channel in &ir.channels {
+            assert!(channel[..scaled.predelay].iter().all(|y| *y == 0.0));
+            assert!(channel.iter().any(|y| y.abs() > 1e-2));
+        }
+
+        let path = std::env::temp_dir().join("oxide_plate_impulse_response.wav");
+        ir.write(&path, SampleFormat::Float32).unwrap();
+        assert_eq!(ir, Audio::read(&path).unwrap());
+        std::fs::remove_file(path).unwrap();
+    }
+}
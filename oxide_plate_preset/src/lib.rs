@@ -0,0 +1,121 @@
+//! Preset files and the factory bank, shared by the plugin and the command-line renderer.
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, fs, io, path::Path};
+
+/// Version of the preset format written by this build.
+pub const PRESET_VERSION: u32 = 1;
+
+/// Factory presets embedded in the binary.
+const FACTORY_BANK: &[&str] = &[
+    include_str!("../presets/bright_plate.json"),
+    include_str!("../presets/dark_plate.json"),
+    include_str!("../presets/large_hall.json"),
+    include_str!("../presets/shimmer_hall.json"),
+    include_str!("../presets/small_room.json"),
+    include_str!("../presets/drum_room.json"),
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Preset {
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Plain parameter values keyed by parameter id.
+    /// Booleans are stored as 0 or 1 and enums as their index.
+    pub params: BTreeMap<String, f32>,
+}
+
+#[derive(Debug)]
+pub enum PresetError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The preset was written by a newer version.
+    Version(u32),
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PresetError::Io(err) => err.fmt(f),
+            PresetError::Json(err) => err.fmt(f),
+            PresetError::Version(version) => {
+                write!(f, "preset version {version} is newer than {PRESET_VERSION}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PresetError {}
+
+impl From<io::Error> for PresetError {
+    fn from(value: io::Error) -> Self {
+        PresetError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for PresetError {
+    fn from(value: serde_json::Error) -> Self {
+        PresetError::Json(value)
+    }
+}
+
+impl Preset {
+    pub fn from_json(json: &str) -> Result<Self, PresetError> {
+        let preset: Preset = serde_json::from_str(json)?;
+        if preset.version > PRESET_VERSION {
+            return Err(PresetError::Version(preset.version));
+        }
+        Ok(preset)
+    }
+
+    pub fn to_json(&self) -> Result<String, PresetError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PresetError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PresetError> {
+        Ok(fs::write(path, self.to_json()?)?)
+    }
+}
+
+/// Returns the factory presets.
+pub fn factory_bank() -> Vec<Preset> {
+    FACTORY_BANK
+        .iter()
+        .map(|json| Preset::from_json(json).expect("factory presets are valid"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factory_bank_parses() {
+        let bank = factory_bank();
+        assert!(!bank.is_empty());
+        for preset in bank {
+            assert_eq!(PRESET_VERSION, preset.version);
+            assert_eq!(
+                preset,
+                Preset::from_json(&preset.to_json().unwrap()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn newer_version() {
+        let json = r#"{ "version": 999, "name": "Future", "params": {} }"#;
+        assert!(matches!(
+            Preset::from_json(json),
+            Err(PresetError::Version(999))
+        ));
+    }
+}